@@ -2,7 +2,7 @@ use bevy::prelude::*;
 use bevy::render::mesh::shape::Cube;
 use bevy::pbr::PbrBundle;
 use bevy::window::WindowMode;
-use goshawk::{rts_camera_system, RtsCamera, ZoomSettings, PanSettings};
+use goshawk::{RtsCameraPlugin, RtsCamera, ZoomSettings, PanSettings};
 use itertools::Itertools;
 
 fn main() {
@@ -16,7 +16,7 @@ fn main() {
         })
         .add_resource(Msaa { samples: 8 })
         .add_plugins(DefaultPlugins)
-        .add_system(rts_camera_system.system())
+        .add_plugin(RtsCameraPlugin)
         .add_system(exit_on_esc.system())
         .add_startup_system(setup.system())
         .run()
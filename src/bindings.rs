@@ -0,0 +1,191 @@
+//! Rebinding the camera's keys from a single map of actions to keys, e.g for a controls menu.
+
+use crate::{PanSettings, PitchSettings, TurnSettings, ZoomSettings};
+use bevy::prelude::*;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// An action of the camera which can be bound to keys.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Action {
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    ZoomIn,
+    ZoomOut,
+    ZoomStepIn,
+    ZoomStepOut,
+    TurnLeft,
+    TurnRight,
+    TurnStepLeft,
+    TurnStepRight,
+    PitchUp,
+    PitchDown,
+}
+
+impl Action {
+    /// All of the actions, in the order in which they are listed in a controls menu.
+    pub const ALL: [Action; 14] = [
+        Action::PanLeft,
+        Action::PanRight,
+        Action::PanUp,
+        Action::PanDown,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::ZoomStepIn,
+        Action::ZoomStepOut,
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::TurnStepLeft,
+        Action::TurnStepRight,
+        Action::PitchUp,
+        Action::PitchDown,
+    ];
+}
+
+/// The keys bound to each action of the camera. The bindings can be read from the key lists of
+/// the settings with [`KeyBindings::from_settings`], and written back with [`KeyBindings::apply`].
+/// Actions without a binding leave the keys in the settings as they are.
+///
+/// # Example
+///
+/// ```
+/// use bevy::prelude::*;
+/// use goshawk::bindings::{Action, KeyBindings};
+/// use goshawk::{PanSettings, PitchSettings, TurnSettings, ZoomSettings};
+///
+/// let (mut zoom, mut pan) = (ZoomSettings::default(), PanSettings::default());
+/// let (mut turn, mut pitch) = (TurnSettings::default(), PitchSettings::default());
+/// let mut bindings = KeyBindings::from_settings(&zoom, &pan, &turn, &pitch);
+/// bindings.bind(Action::PanLeft, vec![KeyCode::J]);
+/// bindings.apply(&mut zoom, &mut pan, &mut turn, &mut pitch);
+///
+/// assert_eq!(&*pan.left_keys, &[KeyCode::J]);
+/// ```
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyBindings {
+    pub keys: HashMap<Action, Vec<KeyCode>>,
+}
+
+impl KeyBindings {
+    pub fn new() -> Self {
+        KeyBindings::default()
+    }
+
+    /// The bindings of all of the actions to the keys currently in the given settings.
+    pub fn from_settings(
+        zoom: &ZoomSettings,
+        pan: &PanSettings,
+        turn: &TurnSettings,
+        pitch: &PitchSettings,
+    ) -> Self {
+        let keys = Action::ALL
+            .iter()
+            .map(|action| {
+                let keys = match action {
+                    Action::PanLeft => &pan.left_keys,
+                    Action::PanRight => &pan.right_keys,
+                    Action::PanUp => &pan.up_keys,
+                    Action::PanDown => &pan.down_keys,
+                    Action::ZoomIn => &zoom.zoom_in_keys,
+                    Action::ZoomOut => &zoom.zoom_out_keys,
+                    Action::ZoomStepIn => &zoom.step_in_keys,
+                    Action::ZoomStepOut => &zoom.step_out_keys,
+                    Action::TurnLeft => &turn.left_keys,
+                    Action::TurnRight => &turn.right_keys,
+                    Action::TurnStepLeft => &turn.step_left_keys,
+                    Action::TurnStepRight => &turn.step_right_keys,
+                    Action::PitchUp => &pitch.up_keys,
+                    Action::PitchDown => &pitch.down_keys,
+                };
+
+                (*action, keys.to_vec())
+            })
+            .collect();
+
+        KeyBindings { keys }
+    }
+
+    /// The keys bound to the given action, which is empty if it has no binding.
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.keys.get(&action).map_or(&[], |keys| keys)
+    }
+
+    /// Bind the given action to the given keys, replacing its previous binding.
+    pub fn bind(&mut self, action: Action, keys: Vec<KeyCode>) {
+        self.keys.insert(action, keys);
+    }
+
+    /// Write the bindings into the key lists of the given settings.
+    pub fn apply(
+        &self,
+        zoom: &mut ZoomSettings,
+        pan: &mut PanSettings,
+        turn: &mut TurnSettings,
+        pitch: &mut PitchSettings,
+    ) {
+        for (action, keys) in &self.keys {
+            let field = match action {
+                Action::PanLeft => &mut pan.left_keys,
+                Action::PanRight => &mut pan.right_keys,
+                Action::PanUp => &mut pan.up_keys,
+                Action::PanDown => &mut pan.down_keys,
+                Action::ZoomIn => &mut zoom.zoom_in_keys,
+                Action::ZoomOut => &mut zoom.zoom_out_keys,
+                Action::ZoomStepIn => &mut zoom.step_in_keys,
+                Action::ZoomStepOut => &mut zoom.step_out_keys,
+                Action::TurnLeft => &mut turn.left_keys,
+                Action::TurnRight => &mut turn.right_keys,
+                Action::TurnStepLeft => &mut turn.step_left_keys,
+                Action::TurnStepRight => &mut turn.step_right_keys,
+                Action::PitchUp => &mut pitch.up_keys,
+                Action::PitchDown => &mut pitch.down_keys,
+            };
+
+            *field = Cow::Owned(keys.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TestSettings;
+
+    #[test]
+    fn settings_round_trip_through_the_bindings() {
+        let mut settings = TestSettings::default();
+        let TestSettings { zoom, pan, turn, pitch } = &settings;
+        let defaults = KeyBindings::from_settings(zoom, pan, turn, pitch);
+        assert_eq!(defaults.keys.len(), Action::ALL.len());
+        assert_eq!(defaults.keys(Action::ZoomIn), &*zoom.zoom_in_keys);
+        assert_eq!(defaults.keys(Action::TurnRight), &*turn.right_keys);
+
+        let mut bindings = KeyBindings::new();
+        bindings.bind(Action::PanUp, vec![KeyCode::I]);
+        bindings.bind(Action::ZoomStepOut, vec![KeyCode::O, KeyCode::P]);
+        bindings.bind(Action::TurnLeft, Vec::new());
+        bindings.bind(Action::PitchDown, vec![KeyCode::K]);
+        assert_eq!(bindings.keys(Action::PanLeft), &[]);
+
+        let TestSettings { zoom, pan, turn, pitch } = &mut settings;
+        bindings.apply(zoom, pan, turn, pitch);
+        assert_eq!(&*settings.pan.up_keys, &[KeyCode::I]);
+        assert_eq!(&*settings.zoom.step_out_keys, &[KeyCode::O, KeyCode::P]);
+        assert_eq!(&*settings.turn.left_keys, &[]);
+        assert_eq!(&*settings.pitch.down_keys, &[KeyCode::K]);
+
+        // Unbound actions keep their keys, and reading the bindings back gives every action
+        let TestSettings { zoom, pan, turn, pitch } = &settings;
+        let read_back = KeyBindings::from_settings(zoom, pan, turn, pitch);
+        for action in Action::ALL.iter() {
+            match bindings.keys.get(action) {
+                Some(keys) => assert_eq!(read_back.keys(*action), &keys[..]),
+                None => assert_eq!(read_back.keys(*action), defaults.keys(*action)),
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Telling quick clicks of a mouse button, such as the scroll wheel, apart from drags.
+
+use bevy::prelude::*;
+
+/// An event sent when the click button is pressed and released quickly without the cursor moving
+/// much, as opposed to being dragged. This can be used for actions such as recentering the camera
+/// on a middle click, distinctly from a middle button drag.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CameraClick {
+    /// The position of the cursor in the window when the button was released
+    pub position: Vec2,
+}
+
+/// The settings for telling clicks apart from drags. This is a resource added by the
+/// [`RtsCameraPlugin`](crate::RtsCameraPlugin).
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ClickSettings {
+    /// The mouse button which is clicked
+    pub button: MouseButton,
+    /// The longest time in seconds for which the button may be held for it to count as a click
+    pub max_secs: f64,
+    /// The furthest in logical pixels which the cursor may move while the button is held for it to
+    /// count as a click
+    pub max_movement: f32,
+}
+
+impl ClickSettings {
+    pub const fn new() -> Self {
+        ClickSettings {
+            button: MouseButton::Middle,
+            max_secs: 0.25,
+            max_movement: 4.0,
+        }
+    }
+}
+
+impl Default for ClickSettings {
+    fn default() -> Self { ClickSettings::new() }
+}
+
+/// The time and cursor position at which the click button was pressed, if it is held.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct ClickState {
+    pressed: Option<(f64, Vec2)>,
+}
+
+/// The system which sends [`CameraClick`] events when the click button in the [`ClickSettings`] is
+/// clicked rather than dragged. This is added by the [`RtsCameraPlugin`](crate::RtsCameraPlugin).
+pub fn camera_click_system(
+    time: Res<Time>,
+    windows: Res<Windows>,
+    buttons: Res<Input<MouseButton>>,
+    settings: Res<ClickSettings>,
+    mut state: Local<ClickState>,
+    mut clicks: ResMut<Events<CameraClick>>,
+) {
+    let cursor = match windows.get_primary().and_then(|window| window.cursor_position()) {
+        Some(cursor) => cursor,
+        None => return,
+    };
+
+    let now = time.seconds_since_startup();
+
+    if buttons.just_pressed(settings.button) {
+        state.pressed = Some((now, cursor));
+    }
+
+    if buttons.just_released(settings.button) {
+        if let Some((pressed_at, pressed_cursor)) = state.pressed.take() {
+            let quick = now - pressed_at <= settings.max_secs;
+            let still = (cursor - pressed_cursor).length() <= settings.max_movement;
+
+            if quick && still {
+                clicks.send(CameraClick { position: cursor });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::window;
+    use bevy::ecs::Stage;
+
+    /// Press the click button with the cursor at `from`, then release it at `to`, returning the
+    /// clicks sent.
+    fn press_and_release(from: Vec2, to: Vec2) -> Vec<CameraClick> {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        let mut windows = Windows::default();
+        windows.add(window(Some(from)));
+        resources.insert(windows);
+        resources.insert(Time::default());
+        resources.insert(Input::<MouseButton>::default());
+        resources.insert(ClickSettings::default());
+        resources.insert(Events::<CameraClick>::default());
+
+        let mut stage = SystemStage::serial().with_system(camera_click_system.system());
+        stage.initialize(&mut world, &mut resources);
+        resources.get_mut::<Input<MouseButton>>().unwrap().press(MouseButton::Middle);
+        stage.run(&mut world, &mut resources);
+
+        let mut buttons = resources.get_mut::<Input<MouseButton>>().unwrap();
+        buttons.update();
+        buttons.release(MouseButton::Middle);
+        drop(buttons);
+        let mut windows = resources.get_mut::<Windows>().unwrap();
+        windows.get_primary_mut().unwrap().update_cursor_position_from_backend(Some(to));
+        drop(windows);
+        stage.run(&mut world, &mut resources);
+
+        let events = resources.get::<Events<CameraClick>>().unwrap();
+        EventReader::default().iter(&events).copied().collect()
+    }
+
+    #[test]
+    fn clicks_are_told_apart_from_drags() {
+        let position = Vec2::new(101.0, 100.0);
+        assert_eq!(press_and_release(Vec2::new(100.0, 100.0), position), vec![CameraClick { position }]);
+        assert_eq!(press_and_release(Vec2::new(100.0, 100.0), Vec2::new(150.0, 100.0)), vec![]);
+    }
+}
@@ -0,0 +1,85 @@
+//! An on-screen readout of the camera state, for tuning and bug reports.
+
+use crate::RtsCamera;
+use bevy::prelude::*;
+
+/// A component which, when attached to an entity with a `Text` (such as one spawned with a
+/// `TextBundle`), makes the [`debug_readout_system`] write the state of the given camera into the
+/// text every frame. The readout can be toggled by adding or removing this component, or by hiding
+/// the text.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct DebugReadout {
+    /// The entity with the [`RtsCamera`] to read out
+    pub camera: Entity,
+}
+
+/// Format the state of the camera for the readout, with angles in degrees.
+pub fn format_readout(camera: &RtsCamera) -> String {
+    let forward = camera.rotation * -Vec3::unit_z();
+    let pitch = (-forward.y).asin();
+    let at = camera.looking_at;
+
+    format!(
+        "looking at: ({:.2}, {:.2}, {:.2})\n\
+         yaw: {:.1}\u{b0}\n\
+         pitch: {:.1}\u{b0}\n\
+         zoom distance: {:.2}\n\
+         pan velocity: ({:.2}, {:.2})\n\
+         zoom velocity: {:.2}\n\
+         turn velocity: {:.2}",
+        at.x,
+        at.y,
+        at.z,
+        camera.yaw.to_degrees(),
+        pitch.to_degrees(),
+        camera.zoom_distance,
+        camera.pan_velocity.x,
+        camera.pan_velocity.y,
+        camera.zoom_velocity,
+        camera.turn_velocity,
+    )
+}
+
+/// The system which writes the camera state into [`DebugReadout`] texts. This is added by the
+/// [`RtsCameraPlugin`](crate::RtsCameraPlugin), and only reads the camera state.
+pub fn debug_readout_system(cameras: Query<&RtsCamera>, mut readouts: Query<(&DebugReadout, &mut Text)>) {
+    for (readout, mut text) in readouts.iter_mut() {
+        if let Ok(camera) = cameras.get(readout.camera) {
+            let value = format_readout(camera);
+
+            // Only write when it differs so that the text is not needlessly laid out again
+            if text.value != value {
+                text.value = value;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_4;
+
+    #[test]
+    fn readout_reflects_the_camera_state() {
+        let camera = RtsCamera {
+            looking_at: Vec3::new(1.0, 2.5, -3.0),
+            yaw: FRAC_PI_4,
+            rotation: Quat::from_rotation_ypr(FRAC_PI_4, -0.5, 0.0),
+            zoom_distance: 42.0,
+            pan_velocity: Vec2::new(0.5, -1.25),
+            zoom_velocity: 3.0,
+            turn_velocity: -0.75,
+            ..Default::default()
+        };
+
+        let expected = "looking at: (1.00, 2.50, -3.00)\n\
+                        yaw: 45.0\u{b0}\n\
+                        pitch: 28.6\u{b0}\n\
+                        zoom distance: 42.00\n\
+                        pan velocity: (0.50, -1.25)\n\
+                        zoom velocity: 3.00\n\
+                        turn velocity: -0.75";
+        assert_eq!(format_readout(&camera), expected);
+    }
+}
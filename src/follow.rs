@@ -0,0 +1,265 @@
+//! Making the camera follow an entity.
+
+use crate::{RtsCamera, ZoomBehavior};
+use bevy::prelude::*;
+
+/// An event sent when the target of a [`Follow`] no longer exists, e.g because the followed unit
+/// died.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct FollowTargetLost {
+    /// The camera entity which was following the target
+    pub camera: Entity,
+    /// The target entity which was lost
+    pub entity: Entity,
+}
+
+/// What the camera does when the target of a [`Follow`] no longer exists.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum OnTargetLost {
+    /// The camera stays where it last saw the target, ignoring panning, until the `Follow` is
+    /// removed or its target is changed
+    Freeze,
+    /// The camera smoothly moves to look at the given point, which is in the same space as
+    /// `looking_at`, and the `Follow` is removed
+    Recenter(Vec3),
+    /// The `Follow` is removed, returning control of the camera to the player
+    FreeControl,
+}
+
+/// A component which, when attached to the camera entity, makes the camera follow the given target
+/// entity by moving `looking_at` towards the target's position every frame. While following, the
+/// target overrides any panning, unless the follow has a `leash`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Follow {
+    /// The entity which the camera is following. This must have a `GlobalTransform`.
+    pub target: Entity,
+    /// The time constant of the smoothing applied to the camera's motion towards the target, in
+    /// seconds. Larger values make the camera trail the target more. If this is zero, the camera
+    /// snaps directly to the target.
+    pub smoothing: f32,
+    /// How far ahead of the target the camera aims, as a multiple of the target's estimated
+    /// velocity. The velocity is estimated from the target's change in position between frames. A
+    /// factor roughly equal to `smoothing` cancels out most of the lag of following a target
+    /// moving at a constant velocity.
+    pub lead_factor: f32,
+    /// The distance from the target beyond which the camera snaps to it rather than smoothly
+    /// moving towards it, so that the camera keeps up when the target teleports across the map. If
+    /// this is `None`, the camera always moves smoothly.
+    pub snap_distance: Option<f32>,
+    /// The distance from the target along the ground within which the player may pan the camera
+    /// manually, e.g to look around a hero in a MOBA. When panning stops, the camera eases back to the target with
+    /// the usual smoothing. If this is `None`, the target overrides any panning.
+    pub leash: Option<f32>,
+    /// The target's position as of the last frame, used to estimate its velocity.
+    pub last_target_position: Option<Vec3>,
+    /// What the camera does when the target no longer exists. A [`FollowTargetLost`] event is sent
+    /// either way.
+    pub on_lost: OnTargetLost,
+    /// Whether the target has been lost. This is updated by the system and should not be modified
+    /// directly.
+    pub lost: bool,
+}
+
+impl Follow {
+    pub fn new(target: Entity) -> Self {
+        Follow {
+            target,
+            smoothing: 0.2,
+            lead_factor: 0.0,
+            snap_distance: None,
+            leash: None,
+            last_target_position: None,
+            on_lost: OnTargetLost::FreeControl,
+            lost: false,
+        }
+    }
+
+    /// Move the camera's `looking_at` towards the target, given its current position.
+    pub(crate) fn update(&mut self, target_position: Vec3, camera: &mut RtsCamera, delta: f32) {
+        // A teleport is not motion, so the camera snaps to the target without leading it
+        let snap_distance = self.snap_distance.unwrap_or(f32::INFINITY);
+        let teleported = camera.looking_at.distance(target_position) > snap_distance;
+
+        let velocity = match self.last_target_position {
+            Some(last) if delta > 0.0 && !teleported => (target_position - last) / delta,
+            _ => Vec3::zero(),
+        };
+
+        self.last_target_position = Some(target_position);
+
+        let goal = target_position + velocity * self.lead_factor;
+        let (_, panning) = camera.input_active;
+
+        // Within the leash, manual panning moves the camera freely, so only keep it on the leash.
+        // The leash is measured along the ground, which the camera pans within.
+        if let Some(leash) = self.leash.filter(|_| panning) {
+            let offset = camera.looking_at - goal;
+            let normal = camera.ground.normal;
+            let offset = offset - normal * offset.dot(normal);
+
+            if offset.length() > leash {
+                camera.looking_at -= offset.normalize() * (offset.length() - leash);
+                camera.pan_velocity = Vec2::zero();
+            }
+
+            return;
+        }
+
+        if self.smoothing <= 0.0 || teleported {
+            camera.looking_at = goal;
+        } else {
+            let factor = 1.0 - (-delta / self.smoothing).exp();
+            camera.looking_at += (goal - camera.looking_at) * factor;
+        }
+    }
+
+    /// Apply the behaviour for when the target no longer exists, returning whether the `Follow`
+    /// should be removed.
+    pub(crate) fn target_lost(&mut self, camera: &mut RtsCamera) -> bool {
+        self.lost = true;
+
+        match self.on_lost {
+            OnTargetLost::Freeze => {
+                if let Some(last) = self.last_target_position {
+                    camera.looking_at = last;
+                }
+
+                camera.pan_velocity = Vec2::zero();
+                false
+            }
+            OnTargetLost::Recenter(point) => {
+                camera.focus_smooth(point, ZoomBehavior::Keep);
+                true
+            }
+            OnTargetLost::FreeControl => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{TestApp, DELTA};
+
+    /// Follow a target moving along X at a constant speed for a few seconds, returning how far the
+    /// camera ends up from it.
+    fn lag_following(follow: &mut Follow) -> f32 {
+        let mut camera = RtsCamera::default();
+        let mut target = Vec3::zero();
+
+        for _ in 0..180 {
+            target += Vec3::new(10.0, 0.0, 0.0) * DELTA;
+            follow.update(target, &mut camera, DELTA);
+        }
+
+        camera.looking_at.distance(target)
+    }
+
+    #[test]
+    fn leading_reduces_the_lag() {
+        let mut trailing = Follow::new(Entity::new(0));
+        let mut leading = Follow { lead_factor: trailing.smoothing, ..Follow::new(Entity::new(0)) };
+
+        let lag = lag_following(&mut trailing);
+        assert!(lag > 1.0);
+        assert!(lag_following(&mut leading) < lag / 10.0);
+    }
+
+    #[test]
+    fn losing_the_target_applies_the_configured_behaviour() {
+        let run = |on_lost: OnTargetLost, pan: bool| {
+            let mut app = TestApp::new();
+            let target = app.world.spawn((GlobalTransform::from_translation(Vec3::new(4.0, 0.0, 0.0)),));
+            let follow = Follow { smoothing: 0.0, on_lost, ..Follow::new(target) };
+            let entity = app.world.spawn((RtsCamera::default(), Transform::default(), follow));
+            let mut reader = app.resources.get::<Events<FollowTargetLost>>().unwrap().get_reader();
+
+            app.update();
+            app.world.despawn(target).unwrap();
+            if pan {
+                app.resources.get_mut::<Input<KeyCode>>().unwrap().press(KeyCode::Up);
+            }
+
+            for _ in 0..3 {
+                app.update();
+            }
+
+            // The event is only sent when the target is first lost
+            let events = app.resources.get::<Events<FollowTargetLost>>().unwrap();
+            let lost: Vec<_> = reader.iter(&events).copied().collect();
+            assert_eq!(lost, vec![FollowTargetLost { camera: entity, entity: target }]);
+            drop(events);
+
+            let follow = app.world.get::<Follow>(entity).ok().cloned();
+            let camera = app.world.get::<RtsCamera>(entity).unwrap();
+            (follow, camera.looking_at, camera.tween.map(|tween| tween.to.looking_at))
+        };
+
+        let (follow, looking_at, _) = run(OnTargetLost::Freeze, true);
+        assert!(follow.unwrap().lost);
+        assert_eq!(looking_at, Vec3::new(4.0, 0.0, 0.0));
+
+        let point = Vec3::new(-10.0, 0.0, 5.0);
+        let (follow, _, to) = run(OnTargetLost::Recenter(point), false);
+        assert!(follow.is_none());
+        assert_eq!(to, Some(point));
+
+        // Control returns to the player, who pans forward
+        let (follow, looking_at, _) = run(OnTargetLost::FreeControl, true);
+        assert!(follow.is_none());
+        assert!(looking_at.z < 0.0);
+    }
+
+    #[test]
+    fn leash_clamps_manual_panning_and_springs_back() {
+        let target = Vec3::new(10.0, 0.0, 0.0);
+        let mut follow = Follow { leash: Some(2.0), ..Follow::new(Entity::new(0)) };
+        let mut camera = RtsCamera { looking_at: target, input_active: (false, true), ..Default::default() };
+
+        // Within the leash, panning is free
+        camera.looking_at = target + Vec3::new(0.0, 0.0, -1.5);
+        follow.update(target, &mut camera, DELTA);
+        assert_eq!(camera.looking_at, target + Vec3::new(0.0, 0.0, -1.5));
+
+        // Beyond it, the target is pulled back to the edge of the leash along the ground
+        camera.looking_at = target + Vec3::new(3.0, 1.0, -4.0);
+        camera.pan_velocity = Vec2::new(1.0, 1.0);
+        follow.update(target, &mut camera, DELTA);
+        assert!(camera.looking_at.abs_diff_eq(target + Vec3::new(1.2, 1.0, -1.6), 1e-5));
+        assert_eq!(camera.pan_velocity, Vec2::zero());
+
+        // When panning stops, the camera eases back to the target
+        camera.input_active = (false, false);
+        let mut distance = camera.looking_at.distance(target);
+        for _ in 0..120 {
+            follow.update(target, &mut camera, DELTA);
+            let next = camera.looking_at.distance(target);
+            assert!(next < distance);
+            distance = next;
+        }
+
+        assert!(distance < 1e-2);
+    }
+
+    #[test]
+    fn teleports_beyond_the_snap_distance_snap_the_camera() {
+        let mut follow = Follow { snap_distance: Some(20.0), smoothing: 0.5, ..Follow::new(Entity::new(0)) };
+        let mut camera = RtsCamera::default();
+        follow.update(Vec3::zero(), &mut camera, DELTA);
+
+        // Small moves are smoothed
+        let step = Vec3::new(1.0, 0.0, 0.0);
+        follow.update(step, &mut camera, DELTA);
+        assert!(camera.looking_at.x > 0.0 && camera.looking_at.x < 1.0);
+
+        // A teleport snaps straight to the target
+        let far = Vec3::new(500.0, 0.0, -300.0);
+        follow.update(far, &mut camera, DELTA);
+        assert_eq!(camera.looking_at, far);
+
+        // Without a snap distance, the camera crawls after it
+        follow.snap_distance = None;
+        follow.update(Vec3::zero(), &mut camera, DELTA);
+        assert!(camera.looking_at.distance(Vec3::zero()) > 100.0);
+    }
+}
@@ -1,44 +1,760 @@
-use bevy::input::mouse::MouseWheel;
+use bevy::core::FixedTimesteps;
+use bitflags::bitflags;
+use bevy::input::mouse::{MouseMotion, MouseWheel};
 use bevy::prelude::*;
+use bevy::render::camera::{Camera, PerspectiveProjection};
+use bevy::window::WindowFocused;
 use std::ops::RangeInclusive;
 use std::borrow::Cow;
-use std::f32::consts::TAU;
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::sync::Arc;
+use util::{clamp, lerp_in_zone, shortest_yaw_delta, Deceleration, Easing, ResponseCurve};
+
+pub mod bindings;
+pub mod click;
+pub mod debug;
+pub mod follow;
+pub mod motion;
+pub mod peek;
+pub mod strategic;
+pub mod tween;
+pub mod util;
+
+pub use click::{CameraClick, ClickSettings};
+pub use debug::DebugReadout;
+pub use follow::{Follow, FollowTargetLost, OnTargetLost};
+use motion::{
+    allowed_yaw_delta, constrain_yaw, Intent, MotionSettings, MotionState, PanMotion, PitchMotion, TurnMotion,
+    ZoomMotion,
+};
+pub use peek::Peek;
+pub use strategic::StrategicView;
+pub use tween::{CameraState, Tween};
+pub use util::{DecelModel, Plane};
 
 // TODO validate settings (e.g ranges)
 
-const SCROLL_TICK_GRACE_SECS: f64 = 0.05;
+/// How much longer than the recent interval between scroll events the adaptive scroll grace period
+/// is, so that deceleration does not kick in between the events of a steady burst of scrolling.
+const SCROLL_GRACE_INTERVAL_FACTOR: f64 = 1.25;
+
+/// The time constant in seconds with which the rendered camera eases towards its tilt toward the
+/// cursor.
+const CURSOR_TILT_EASE_SECS: f32 = 0.15;
+
+/// The smoothed turn input rate below which it is treated as having settled at zero, as otherwise
+/// it would only approach zero asymptotically and the camera would never count as idle.
+const TURN_INPUT_RATE_EPSILON: f32 = 1e-3;
+
+/// The most steps taken to move the target or pull the smoothed focus along to keep a point within
+/// the screen margin.
+const FOCUS_MARGIN_STEPS: usize = 4;
+
+/// The turn speed in radians per second below which a released turn starts settling onto the turn
+/// settings' `snap_on_release` increment.
+const TURN_SNAP_SPEED: f32 = 0.05;
+
+/// The time constant in seconds with which a released turn eases onto the snap increment.
+const TURN_SNAP_EASE_SECS: f32 = 0.15;
+
+/// How close in radians the yaw must come to the snap increment while easing onto it to snap.
+const TURN_SNAP_EPSILON: f32 = 1e-4;
+
+/// How close the zoom distance or yaw must be to a limit to count as being at it, to allow for
+/// floating point error in the state.
+const LIMIT_EPSILON: f32 = 1e-4;
+
+bitflags! {
+    /// Which kinds of player input the camera responds to. The narrower flags only take effect
+    /// along with the broader flag they belong to, e.g `EDGE_PAN` requires `PAN`.
+    pub struct CameraControls: u32 {
+        /// Panning with the keyboard or the mouse at the edges of the screen
+        const PAN = 1;
+        /// Panning with the mouse at the edges of the screen
+        const EDGE_PAN = 1 << 1;
+        /// Zooming with the scroll wheel or the keyboard
+        const ZOOM = 1 << 2;
+        /// Zooming with the scroll wheel
+        const SCROLL_ZOOM = 1 << 3;
+        /// Turning with the keyboard or the mouse in the turn margin
+        const TURN = 1 << 4;
+        /// Turning with the mouse in the turn margin
+        const MOUSE_TURN = 1 << 5;
+        /// Pitching with the keyboard
+        const PITCH = 1 << 6;
+        const NONE = 0;
+        const ALL = Self::PAN.bits | Self::EDGE_PAN.bits | Self::ZOOM.bits | Self::SCROLL_ZOOM.bits
+            | Self::TURN.bits | Self::MOUSE_TURN.bits | Self::PITCH.bits;
+    }
+}
+
+impl Default for CameraControls {
+    fn default() -> Self { CameraControls::ALL }
+}
+
+/// The smallest radius which [`RtsCamera::focus_on_centroid_framed`] frames around the points, so
+/// that a single point is not framed from as close as possible.
+pub const MIN_FRAME_RADIUS: f32 = 1.0;
+
+/// The minimum distance of the camera from its target. Closer than this, the view direction
+/// degenerates, so the camera distance is kept at least this far even if the zoom settings'
+/// distance range starts closer.
+pub const MIN_CAMERA_DISTANCE: f32 = 0.01;
+
+/// The priority of player input. Scripted moves acquire the camera at a higher priority to block
+/// player input until they release it. See [`RtsCamera::acquire`].
+pub const PLAYER_PRIORITY: u32 = 0;
+
+/// A plugin which adds the [`rts_camera_system`] along with the [`RtsCameraDefaults`] and
+/// [`CameraTimestep`] resources it requires. The system is added to the update stage; to run it in
+/// a fixed timestep stage instead, see [`CameraTimestep`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RtsCameraPlugin;
+
+impl RtsCameraPlugin {
+    /// A plugin like the [`RtsCameraPlugin`], but which only runs the [`rts_camera_system`] while the
+    /// app is in the given state, e.g so that the camera is frozen while the game is paused. Outside
+    /// of the state, the camera holds its transform. The given stage must be a `StateStage` for the
+    /// state type, which the app must already have:
+    ///
+    /// ```no_run
+    /// use bevy::prelude::*;
+    /// use goshawk::RtsCameraPlugin;
+    ///
+    /// #[derive(Clone)]
+    /// enum GameState {
+    ///     Playing,
+    ///     Paused,
+    /// }
+    ///
+    /// App::build()
+    ///     .add_resource(State::new(GameState::Playing))
+    ///     .add_stage_after(stage::UPDATE, "game_state", StateStage::<GameState>::default())
+    ///     .add_plugin(RtsCameraPlugin::run_in_state("game_state", GameState::Playing))
+    ///     .run();
+    /// ```
+    pub fn run_in_state<S>(stage: &'static str, state: S) -> RtsCameraStatePlugin<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        RtsCameraStatePlugin { stage, state }
+    }
+}
+
+impl Plugin for RtsCameraPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        add_shared(app).add_system(rts_camera_system.system());
+    }
+}
+
+/// A plugin which only runs the [`rts_camera_system`] while the app is in the given state. See
+/// [`RtsCameraPlugin::run_in_state`].
+#[derive(Clone, Debug)]
+pub struct RtsCameraStatePlugin<S> {
+    /// The name of the `StateStage` in which to run the system
+    pub stage: &'static str,
+    /// The state in which to run the system
+    pub state: S,
+}
+
+impl<S: Clone + Send + Sync + 'static> Plugin for RtsCameraStatePlugin<S> {
+    fn build(&self, app: &mut AppBuilder) {
+        add_shared(app).on_state_update(self.stage, self.state.clone(), rts_camera_system.system());
+    }
+}
+
+/// A plugin which adds everything that the [`RtsCameraPlugin`] adds except the
+/// [`rts_camera_system`] itself: the resources and events which the system requires, and the
+/// supporting systems. This is for registering the system by hand, e.g in a fixed timestep stage as
+/// described for [`CameraTimestep`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RtsCameraSharedPlugin;
+
+impl Plugin for RtsCameraSharedPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        add_shared(app);
+    }
+}
+
+/// Add the resources, events and supporting systems which all of the camera plugins add.
+fn add_shared(app: &mut AppBuilder) -> &mut AppBuilder {
+    app.init_resource::<RtsCameraDefaults>()
+        .init_resource::<CameraTimestep>()
+        .init_resource::<ClickSettings>()
+        .add_event::<CameraClick>()
+        .add_event::<FollowTargetLost>()
+        .add_system(far_plane_validation_system.system())
+        .add_system(distance_range_validation_system.system())
+        .add_system(debug::debug_readout_system.system())
+        .add_system(click::camera_click_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, strategic::strategic_view_removal_system.system())
+}
+
+/// The time step with which [`rts_camera_system`] simulates the camera. By default, the frame
+/// delta time is used. For deterministic simulation, the system may instead be run in a stage with
+/// a labelled `FixedTimestep` run criteria, using the step of that timestep. In that case, register
+/// the system by hand with the [`RtsCameraSharedPlugin`] rather than the [`RtsCameraPlugin`]:
+///
+/// ```no_run
+/// use bevy::core::FixedTimestep;
+/// use bevy::prelude::*;
+/// use goshawk::{rts_camera_system, CameraTimestep, RtsCameraSharedPlugin};
+///
+/// App::build()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugin(RtsCameraSharedPlugin)
+///     .add_resource(CameraTimestep::Fixed("camera".into()))
+///     .add_stage_after(
+///         stage::UPDATE,
+///         "camera_fixed_update",
+///         SystemStage::parallel()
+///             .with_run_criteria(FixedTimestep::step(1.0 / 60.0).with_label("camera"))
+///             .with_system(rts_camera_system.system()),
+///     )
+///     .run();
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub enum CameraTimestep {
+    /// The frame delta time from `Time`
+    Frame,
+    /// The step of the `FixedTimestep` with the given label. If there is no such timestep, the
+    /// frame delta time is used.
+    Fixed(Cow<'static, str>),
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for CameraTimestep {
+    fn default() -> Self {
+        CameraTimestep::Frame
+    }
+}
+
+impl CameraTimestep {
+    fn delta(&self, time: &Time, fixed_timesteps: &FixedTimesteps) -> f32 {
+        match self {
+            CameraTimestep::Frame => time.delta_seconds(),
+            CameraTimestep::Fixed(label) => fixed_timesteps
+                .get(label)
+                .map_or(time.delta_seconds(), |fixed| fixed.step() as f32),
+        }
+    }
+}
+
+/// A set of settings which temporarily replace a camera's settings. Any settings which are `None`
+/// are not overridden.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct SettingsOverride {
+    pub zoom: Option<ZoomSettings>,
+    pub pan: Option<PanSettings>,
+    pub turn: Option<TurnSettings>,
+    pub pitch: Option<PitchSettings>,
+}
+
+/// The limits on the speed of a camera currently in effect, as returned by
+/// [`RtsCamera::effective_limits`]. These are in world units or radians per second of real time.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct EffectiveLimits {
+    /// The maximum speed at which the target moves while panning
+    pub max_pan_speed: f32,
+    /// The maximum speed at which the zoom distance changes
+    pub max_zoom_velocity: f32,
+    /// The maximum speed in radians per second at which the camera turns
+    pub max_turn_speed: f32,
+}
+
+/// The settings used for cameras which do not have the corresponding settings component attached.
+/// Modifying this resource changes the settings of all such cameras.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct RtsCameraDefaults {
+    pub zoom: ZoomSettings,
+    pub pan: PanSettings,
+    pub turn: TurnSettings,
+    pub pitch: PitchSettings,
+}
+
+/// The settings of each kind which apply to a camera this frame.
+#[derive(Copy, Clone, Debug)]
+struct CameraSettings<'a> {
+    zoom: &'a ZoomSettings,
+    pan: &'a PanSettings,
+    turn: &'a TurnSettings,
+    pitch: &'a PitchSettings,
+}
+
+impl<'a> CameraSettings<'a> {
+    /// The part of the settings which the motion model uses.
+    fn motion(&self) -> MotionSettings<'a> {
+        MotionSettings {
+            zoom: self.zoom.motion(),
+            pan: self.pan.motion(),
+            turn: self.turn.motion(),
+            pitch: self.pitch.motion(),
+        }
+    }
+}
 
 /// The system which manages the RTS camera state and manipulates the attached camera transform.
+/// This requires the [`RtsCameraDefaults`] and [`CameraTimestep`] resources and the
+/// [`FollowTargetLost`] events, which are added by the camera plugins (see
+/// [`RtsCameraSharedPlugin`]). It also requires the `Time`, `FixedTimesteps`, `Windows` and
+/// `Input<KeyCode>` resources and the `MouseWheel`, `MouseMotion` and `WindowFocused` events, which
+/// are added by bevy's core, window and input plugins.
+///
+/// The camera entity may be the child of a rig entity, so that other systems can move the whole
+/// rig. In that case, the camera state such as `looking_at` is relative to the parent, and the
+/// written transform is the local transform, so that the camera's world transform is the same as
+/// it would be without a parent, offset by the parent's transform. [`Follow`] targets are
+/// converted into the parent's space.
+///
+/// The system can also run headless, e.g in tests or on a server, as long as all of the above exist.
+/// The `Windows` resource may be empty, such as `Windows::default()`. Without a primary window,
+/// cursor input is skipped and the viewport size is taken from the camera's [`ViewportSize`]
+/// component.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 pub fn rts_camera_system(
+    commands: &mut Commands,
     time: Res<Time>,
     windows: Res<Windows>,
     cursor_scroll_events: Res<Events<MouseWheel>>,
+    mouse_motion_events: Res<Events<MouseMotion>>,
+    window_focus_events: Res<Events<WindowFocused>>,
     keyboard: Res<Input<KeyCode>>,
-    mut query: Query<(&mut RtsCamera, &mut Transform, Option<&ZoomSettings>, Option<&PanSettings>, Option<&TurnSettings>)>,
+    defaults: Res<RtsCameraDefaults>,
+    timestep: Res<CameraTimestep>,
+    fixed_timesteps: Res<FixedTimesteps>,
+    mut follow_lost_events: ResMut<Events<FollowTargetLost>>,
+    mut query: Query<(
+        Entity,
+        &mut RtsCamera,
+        &mut Transform,
+        (Option<&ZoomSettings>, Option<&PanSettings>, Option<&TurnSettings>, Option<&PitchSettings>),
+        Option<&mut ZoomLevel>,
+        Option<&mut Follow>,
+        (Option<&mut Peek>, Option<&mut StrategicView>),
+        (Option<&IdleCinematic>, Option<&ExternalControl>),
+        Option<&ViewportSize>,
+        Option<(&ClipAdjust, &mut PerspectiveProjection)>,
+        (Option<&PixelSnap>, Option<&TransformHook>),
+        (Option<&PanBounds>, Option<&Terrain>),
+        Option<&Camera>,
+        Option<&Parent>,
+    )>,
+    targets: Query<&GlobalTransform>,
 ) {
-    static DEFAULT_ZOOM: ZoomSettings = ZoomSettings::new();
-    static DEFAULT_PAN: PanSettings = PanSettings::new();
-    static DEFAULT_TURN: TurnSettings = TurnSettings::new();
-
-    for (mut camera, mut transform, zoom, pan, turn) in query.iter_mut() {
-        let window = windows.get_primary().unwrap();
-        let cursor = match window.cursor_position() {
-            Some(pos) => pos,
-            None => return,
+    let window = windows.get_primary();
+
+    for (
+        entity,
+        mut camera,
+        mut transform,
+        settings,
+        zoom_level,
+        follow,
+        (peek, strategic),
+        (cinematic, external),
+        viewport,
+        clip,
+        (pixel_snap, hook),
+        (bounds, terrain),
+        camera_component,
+        parent,
+    ) in query.iter_mut()
+    {
+        let (zoom, pan, turn, pitch) = settings;
+        let delta = timestep.delta(&time, &fixed_timesteps);
+        let (cursor, viewport) = match window {
+            Some(window) => (window.cursor_position(), Vec2::new(window.width(), window.height())),
+            None => (None, viewport.map_or(Vec2::zero(), |v| v.0)),
         };
 
-        let zoom = zoom.unwrap_or(&DEFAULT_ZOOM);
-        let pan = pan.unwrap_or(&DEFAULT_PAN);
-        let turn = turn.unwrap_or(&DEFAULT_TURN);
+        let rig = parent.and_then(|parent| targets.get(parent.0).ok());
+        camera.rig_transform = rig.map_or(Mat4::identity(), |rig| rig.compute_matrix());
+
+        // Peek and the strategic view push and pop settings overrides, so they update before the
+        // active override is chosen. The strategic view transitions from the zoom settings without
+        // any override.
+        if external.is_none() {
+            if let Some(mut peek) = peek {
+                peek.update(&mut camera, &keyboard);
+            }
+
+            if let Some(mut strategic) = strategic {
+                strategic.update(&mut camera, &keyboard, zoom.unwrap_or(&defaults.zoom));
+            }
+        }
+
+        // The override stack is moved out while the camera is updated, so that the active override
+        // can be borrowed alongside the camera rather than cloned. It is put back afterwards.
+        let overrides = std::mem::take(&mut camera.settings_overrides);
+        let active = overrides.last();
+        let zoom = active.and_then(|o| o.zoom.as_ref()).or(zoom).unwrap_or(&defaults.zoom);
+        let pan = active.and_then(|o| o.pan.as_ref()).or(pan).unwrap_or(&defaults.pan);
+        let turn = active.and_then(|o| o.turn.as_ref()).or(turn).unwrap_or(&defaults.turn);
+        let pitch = active.and_then(|o| o.pitch.as_ref()).or(pitch).unwrap_or(&defaults.pitch);
 
         // TODO handle pixel units
-        let scroll = camera.cursor_scroll_event_reader.latest(&cursor_scroll_events).map(|e| e.y);
+        let scroll = match zoom.scroll_accumulation {
+            ScrollAccumulation::Latest => {
+                let latest = camera.cursor_scroll_event_reader.latest(&cursor_scroll_events);
+                latest.map(|e| Vec2::new(e.x, e.y))
+            }
+            ScrollAccumulation::Sum => camera
+                .cursor_scroll_event_reader
+                .iter(&cursor_scroll_events)
+                .map(|e| Vec2::new(e.x, e.y))
+                .fold(None, |sum, scroll| Some(sum.unwrap_or_else(Vec2::zero) + scroll)),
+        };
+        let motion = camera.mouse_motion_event_reader.iter(&mouse_motion_events).map(|e| e.delta);
+        camera.mouse_motion = motion.fold(Vec2::zero(), |sum, delta| sum + delta);
+
+        let primary_id = window.map(|window| window.id());
+        let focus_events = camera.window_focus_event_reader.iter(&window_focus_events);
+        if let Some(focus) = focus_events.rev().find(|e| Some(e.id) == primary_id) {
+            camera.window_focused = focus.focused;
+        }
+
+        // While frozen, nothing about the camera changes, including the following and the render
+        // easing, and input events are discarded
+        if camera.freeze_on_unfocus && !camera.window_focused {
+            camera.settings_overrides = overrides;
+            continue;
+        }
+
+        if external.is_some() {
+            camera.apply_external_state(zoom);
+        } else {
+            // Targets are given in world space, but the camera state is relative to its parent rig
+            let world_to_rig = camera.rig_transform.inverse();
+            let to_rig_space = |target: &GlobalTransform| world_to_rig.transform_point3(target.translation);
+
+            if let Some(target) = camera.focus_entity.take() {
+                match targets.get(target) {
+                    Ok(transform) => camera.focus_smooth(to_rig_space(transform), ZoomBehavior::Keep),
+                    Err(_) => warn!("RtsCamera cannot look at entity {:?} as it has no GlobalTransform", target),
+                }
+            }
+
+            let settings = CameraSettings { zoom, pan, turn, pitch };
+            let now = time.seconds_since_startup();
+
+            camera.tick(scroll, cursor, viewport, &keyboard, &settings, cinematic, delta, now);
+
+            if let Some(mut follow) = follow {
+                if let Ok(target) = targets.get(follow.target) {
+                    follow.lost = false;
+                    follow.update(to_rig_space(target), &mut camera, delta);
+                } else {
+                    if !follow.lost {
+                        follow_lost_events.send(FollowTargetLost { camera: entity, entity: follow.target });
+                    }
+
+                    if follow.target_lost(&mut camera) {
+                        commands.remove_one::<Follow>(entity);
+                    }
+                }
+            }
+
+            if let Some(bounds) = bounds {
+                let desired = camera.looking_at;
+                camera.apply_bounds(bounds, delta);
+
+                if let (Some(window), Some(camera_component)) = (window, camera_component) {
+                    camera.keep_in_margin(desired, window, camera_component);
+                }
+            }
+
+            if let Some(terrain) = terrain {
+                camera.apply_clearance(terrain);
+            }
+        }
+
+        if let Some(mut zoom_level) = zoom_level {
+            let range = &zoom.distance_range;
+            let width = range.end() - range.start();
+
+            // A range with equal ends has no meaningful fraction, so it is treated as fully zoomed in
+            let fraction = if width > 0.0 { (camera.zoom_distance - range.start()) / width } else { 0.0 };
+
+            // Only write when it differs so that change detection is meaningful
+            if zoom_level.0 != fraction {
+                zoom_level.0 = fraction;
+            }
+        }
+
+        if let Some((clip_adjust, mut projection)) = clip {
+            let near = lerp_in_zone(camera.zoom_distance, &zoom.distance_range, &clip_adjust.near_range);
+            let far = lerp_in_zone(camera.zoom_distance, &zoom.distance_range, &clip_adjust.far_range);
+
+            // Only write when it differs so that the projection matrix is not needlessly recomputed
+            if projection.near != near || projection.far != far {
+                projection.near = near;
+                projection.far = far;
+            }
+        }
+
+        camera.settings_overrides = overrides;
+        camera.update_render_focus(delta);
+
+        if let (Some(window), Some(camera_component)) = (window, camera_component) {
+            camera.keep_target_in_margin(window, camera_component);
+        }
+        let mut rendered = camera.camera_transform();
+        rendered.translation += camera.render_offset;
+
+        camera.update_render_tilt(cursor, viewport, delta);
+        let tilt = camera.render_tilt;
+        rendered.rotation *= Quat::from_rotation_ypr(-tilt.x, tilt.y, 0.0);
+        rendered = camera.smooth_rendered(rendered, delta);
+
+        if let Some(snap) = pixel_snap {
+            rendered.translation = snap.apply(rendered.translation);
+        }
+
+        if let Some(hook) = hook {
+            rendered = (hook.0)(&camera, rendered);
+        }
+
+        *transform = rendered;
+        camera.rendered_transform = Some(rendered);
+    }
+}
+
+/// A system which checks that the far clip plane of newly added cameras is far enough away to see
+/// the ground at the maximum zoom distance, warning if it is not. If the zoom settings'
+/// `auto_extend_far_plane` is set, the far plane is extended instead. Cameras with [`ClipAdjust`]
+/// are skipped, as it manages the far plane itself. This is added by the [`RtsCameraPlugin`].
+#[allow(clippy::type_complexity)]
+pub fn far_plane_validation_system(
+    defaults: Res<RtsCameraDefaults>,
+    mut query: Query<
+        (Option<&ZoomSettings>, &mut PerspectiveProjection),
+        (Added<RtsCamera>, Without<ClipAdjust>),
+    >,
+) {
+    for (zoom, mut projection) in query.iter_mut() {
+        let zoom = zoom.unwrap_or(&defaults.zoom);
+        let required_far = zoom.required_far_plane(projection.fov);
+
+        if projection.far >= required_far {
+            continue;
+        }
+
+        if zoom.auto_extend_far_plane {
+            projection.far = required_far;
+        } else {
+            warn!(
+                "RTS camera far plane ({}) is too close to see the ground at the maximum zoom distance ({}). \
+                 The far plane should be at least {}, or `auto_extend_far_plane` should be set.",
+                projection.far,
+                zoom.distance_range.end(),
+                required_far,
+            );
+        }
+    }
+}
+
+/// A system which checks that the zoom distance range of newly added cameras starts further away than
+/// [`MIN_CAMERA_DISTANCE`], warning if it does not, as the camera is never moved closer to its target
+/// than that. This is added by the [`RtsCameraPlugin`].
+pub fn distance_range_validation_system(
+    defaults: Res<RtsCameraDefaults>,
+    query: Query<Option<&ZoomSettings>, Added<RtsCamera>>,
+) {
+    for zoom in query.iter() {
+        let zoom = zoom.unwrap_or(&defaults.zoom);
+
+        if *zoom.distance_range.start() < MIN_CAMERA_DISTANCE {
+            warn!(
+                "RTS camera zoom distance range starts at {}, which is closer than the minimum camera distance \
+                 ({}). The camera will not move closer than the minimum distance.",
+                zoom.distance_range.start(),
+                MIN_CAMERA_DISTANCE,
+            );
+        }
+    }
+}
+
+/// A component which, when attached to the camera entity, is updated every frame with how far the
+/// camera is zoomed out, where 0 is the minimum of the zoom distance range and 1 is the maximum. It
+/// is only marked as changed when the zoom level actually changes, so it can be used with change
+/// detection to drive LOD, UI, etc. If the range has equal ends, the zoom level is 0.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct ZoomLevel(pub f32);
+
+/// A component which, when attached to a camera entity along with a `PerspectiveProjection`,
+/// adjusts the projection's near and far clip planes according to the zoom distance. The start of
+/// each range is used at the minimum zoom distance, and the end at the maximum, linearly
+/// interpolating between them. If the zoom distance range has equal ends, the start of each range
+/// is used.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ClipAdjust {
+    /// The near clip plane distance at the minimum and maximum zoom distance
+    pub near_range: RangeInclusive<f32>,
+    /// The far clip plane distance at the minimum and maximum zoom distance
+    pub far_range: RangeInclusive<f32>,
+}
+
+/// The region of the XZ plane within which [`PanBounds`] keeps the camera target.
+#[derive(Clone)]
+pub enum BoundsShape {
+    /// A rectangle, given by the minimum and maximum X and Z coordinates of the target
+    Rect { min: Vec2, max: Vec2 },
+    /// A circle, e.g for a circular map
+    Circle { center: Vec2, radius: f32 },
+    /// Any other region, given by a function mapping X and Z coordinates to the nearest
+    /// coordinates within the region. Points within the region should be mapped to themselves.
+    Custom(Arc<dyn Fn(Vec2) -> Vec2 + Send + Sync>),
+}
 
-        camera.tick(scroll, cursor, window, &keyboard, zoom, pan, turn, &time);
-        *transform = camera.camera_transform();
+impl BoundsShape {
+    /// The nearest point within the region to the given point.
+    pub fn constrain(&self, point: Vec2) -> Vec2 {
+        match self {
+            BoundsShape::Rect { min, max } => {
+                Vec2::new(clamp(point.x, &(min.x..=max.x)), clamp(point.y, &(min.y..=max.y)))
+            }
+            BoundsShape::Circle { center, radius } => {
+                let offset = point - *center;
+
+                if offset.length() > *radius {
+                    *center + offset.normalize() * *radius
+                } else {
+                    point
+                }
+            }
+            BoundsShape::Custom(constrain) => constrain(point),
+        }
+    }
+}
+
+/// A component which, when attached to the camera entity, keeps the camera target within a
+/// region of the XZ plane. On tilted ground, the target is kept above or below the region at the
+/// same height above the ground. Any pan velocity out of the region is stopped.
+#[derive(Clone)]
+pub struct PanBounds {
+    /// The region within which the target is kept
+    pub shape: BoundsShape,
+    /// The size of the bump applied to the rendered transform when the camera hits the edge of the
+    /// bounds, as a multiple of the pan velocity which was stopped. This is purely cosmetic, giving
+    /// tactile feedback for hitting the edge of the map. If this is zero, there is no bump.
+    pub bump: f32,
+    /// The time constant of the decay of the bump in seconds
+    pub bump_decay: f32,
+}
+
+impl PanBounds {
+    /// Bounds keeping the target within the rectangle between the given minimum and maximum X and
+    /// Z coordinates.
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        PanBounds::with_shape(BoundsShape::Rect { min, max })
+    }
+
+    /// Bounds keeping the target within the circle of the given radius around `center`.
+    pub fn circle(center: Vec2, radius: f32) -> Self {
+        PanBounds::with_shape(BoundsShape::Circle { center, radius })
+    }
+
+    /// Bounds keeping the target within a custom region. See [`BoundsShape::Custom`].
+    pub fn custom(constrain: impl Fn(Vec2) -> Vec2 + Send + Sync + 'static) -> Self {
+        PanBounds::with_shape(BoundsShape::Custom(Arc::new(constrain)))
+    }
+
+    /// Bounds keeping the target within the given region.
+    pub fn with_shape(shape: BoundsShape) -> Self {
+        PanBounds {
+            shape,
+            bump: 0.0,
+            bump_decay: 0.1,
+        }
+    }
+}
+
+/// A component which, when attached to the camera entity, describes the height of the terrain so
+/// that the camera can be kept above it. Only the camera itself is raised; the target is left
+/// where it is.
+#[derive(Clone)]
+pub struct Terrain {
+    /// The height of the terrain at the given X and Z coordinates
+    pub height_at: Arc<dyn Fn(Vec2) -> f32 + Send + Sync>,
+    /// The minimum height of the camera above the terrain directly under it. When flying low over
+    /// a hill, the camera moves further away from the target along its line of sight to keep this
+    /// clearance.
+    pub min_clearance: f32,
+}
+
+impl Terrain {
+    pub fn new(height_at: impl Fn(Vec2) -> f32 + Send + Sync + 'static, min_clearance: f32) -> Self {
+        Terrain {
+            height_at: Arc::new(height_at),
+            min_clearance,
+        }
+    }
+}
+
+/// A component which, when attached to the camera entity, post-processes the transform which the
+/// system writes to the camera, e.g to apply a custom shake or an offset for VR. The function is
+/// given the camera and its computed transform, after all of the built in effects such as pixel
+/// snapping, and returns the transform to write. This only affects the written transform, not the
+/// camera state.
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub struct TransformHook(pub Arc<dyn Fn(&RtsCamera, Transform) -> Transform + Send + Sync>);
+
+impl TransformHook {
+    pub fn new(hook: impl Fn(&RtsCamera, Transform) -> Transform + Send + Sync + 'static) -> Self {
+        TransformHook(Arc::new(hook))
+    }
+}
+
+/// A component which, when attached to the camera entity, snaps the translation of the rendered
+/// camera transform to a grid, so that sprites in pixel art games do not shimmer from sub-pixel
+/// camera movement. Only the written transform is snapped; the camera state such as `looking_at`
+/// stays continuous so that motion remains smooth over time.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PixelSnap {
+    /// The size of a pixel in world units, which is the spacing of the grid snapped to
+    pub units_per_pixel: f32,
+}
+
+impl PixelSnap {
+    fn apply(&self, translation: Vec3) -> Vec3 {
+        if self.units_per_pixel <= 0.0 {
+            return translation;
+        }
+
+        let pixels = translation / self.units_per_pixel;
+        Vec3::new(pixels.x.round(), pixels.y.round(), pixels.z.round()) * self.units_per_pixel
     }
 }
 
+/// A component giving the size of the viewport in logical pixels for headless use, when there is no
+/// primary window to take the size from.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct ViewportSize(pub Vec2);
+
+/// A component which, when attached to the camera entity, makes the camera slowly pan and turn by
+/// itself once there has been no input for a while, e.g for menus and attract screens. Control
+/// returns to the player as soon as there is any input, and the camera then decelerates as usual.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct IdleCinematic {
+    /// The time in seconds without input before the cinematic motion starts
+    pub delay: f32,
+    /// The speed at which the camera pans to the right while the cinematic is active. This is
+    /// subject to the pan settings' maximum speed.
+    pub pan_speed: f32,
+    /// The speed in radians per second at which the camera turns left while the cinematic is
+    /// active. This is subject to the turn settings' maximum speed.
+    pub turn_speed: f32,
+}
+
+/// A marker component which, when attached to the camera entity, hands control of the camera to
+/// other code, e.g for replays and tools. The system then ignores input, velocities, follows and
+/// bounds for the camera, and only derives its rotation and transform from the `looking_at`, `yaw`,
+/// `pitch_offset` and `zoom_distance` set on it, so that it exactly tracks the state it is given.
+/// Pitch smoothing is not applied, though any smoothing of the rendered transform still is.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct ExternalControl;
+
 
 pub struct RtsCamera {
     /// Where the camera is looking (its target)
@@ -49,18 +765,202 @@ pub struct RtsCamera {
     pub rotation: Quat,
     /// The angle which the camera has turned to the right in radians
     pub yaw: f32,
+    /// The manual adjustment to the pitch in radians, on top of the pitch derived from the zoom
+    /// distance. This is controlled by the pitch settings.
+    pub pitch_offset: f32,
+    /// The pitch in radians derived from the zoom distance, which lags behind the zoom distance
+    /// when the zoom settings' `pitch_smoothing` is enabled. This is updated by the system and
+    /// should not be modified directly. It is `None` until the first update.
+    pub zoom_pitch: Option<f32>,
     /// The velocity at which the camera is zooming in or out
     pub zoom_velocity: f32,
     /// The velocity at which the camera is panning
     pub pan_velocity: Vec2,
     pub turn_velocity: f32,
     /// The last time the scroll wheel sent a scroll event. It is treated as still having sent input
-    /// for 0.05s after the last event, as otherwise idle deceleration kicks in too soon and scrolling
-    /// is too slow.
+    /// for a grace period after the last event, as otherwise idle deceleration kicks in too soon and
+    /// scrolling is too slow. See the zoom settings' `scroll_grace_secs`. This is negative infinity
+    /// until the first scroll event, so that there is no grace period at startup.
     pub last_scroll_sec: f64,
-    /// The distance which the camera is from the target
+    /// The interval in seconds between the last two scroll events, used to adapt the scroll grace
+    /// period to the rate of scrolling
+    pub scroll_interval_secs: f64,
+    /// The last time the scroll wheel panned the camera while the pan settings'
+    /// `scroll_pan_modifier` was held. Like `last_scroll_sec`, there is a grace period after it
+    /// during which the pan is not decelerated.
+    pub last_scroll_pan_sec: f64,
+    /// The interval in seconds between the last two scroll pan events
+    pub scroll_pan_interval_secs: f64,
+    /// The part of the scroll impulses which has not yet been applied to the zoom velocity, when
+    /// the zoom settings' `scroll_smoothing` is enabled
+    pub pending_scroll: f32,
+    /// The rate per second at which the pending scroll impulse is applied to the zoom velocity
+    pub pending_scroll_rate: f32,
+    /// How far the camera is zoomed out, within the zoom settings' distance range. Unless the zoom
+    /// settings have a `distance_change_zone`, this is the distance which the camera is from the
+    /// target.
     pub zoom_distance: f32,
+    /// The distance which the camera is from the target. This is updated from the zoom distance and
+    /// zoom settings, and must **not** be modified directly by the user. Rather, modify the zoom
+    /// distance.
+    pub camera_distance: f32,
+    /// Whether the zoom distance is easing back into the zoom settings' distance range after the
+    /// range changed, rather than being clamped to it. This is updated by the system and should not
+    /// be modified directly.
+    pub zoom_range_return: bool,
+    /// The zoom settings' distance range as of the last update, to detect when it changes. This is
+    /// updated by the system and should not be modified directly.
+    pub last_distance_range: Option<RangeInclusive<f32>>,
     pub cursor_scroll_event_reader: EventReader<MouseWheel>,
+    pub mouse_motion_event_reader: EventReader<MouseMotion>,
+    pub window_focus_event_reader: EventReader<WindowFocused>,
+    /// Whether the primary window currently has focus. This is updated by the system and should not
+    /// be modified directly.
+    pub window_focused: bool,
+    /// Whether to freeze the camera while the primary window does not have focus, e.g while
+    /// alt-tabbed, so that it is exactly where it was left when focus returns. Nothing is updated
+    /// while frozen: not the motion, following, bounds or the easing of the rendered transform.
+    /// The velocities are kept, so any motion carries on from where it stopped.
+    pub freeze_on_unfocus: bool,
+    /// The magnitudes of pan input last frame, to detect when pan input in a direction starts for
+    /// the pan settings' `flick_impulse`. This is updated by the system and should not be modified
+    /// directly.
+    pub last_pan_magnitudes: PanMagnitudes,
+    /// How the mouse drives the camera. With [`MouseInput::Relative`], the cursor position is
+    /// ignored, which is the correct input source while the cursor is grabbed or hidden.
+    pub mouse_input: MouseInput,
+    /// The total relative mouse motion in this frame. This is updated by the system and should not
+    /// be modified directly.
+    pub mouse_motion: Vec2,
+    /// The cursor position as of the last update, used to tell which way the cursor is moving. This
+    /// is updated by the system and should not be modified directly.
+    pub last_cursor: Option<Vec2>,
+    /// Whether the camera should respond to scroll, mouse edge and keyboard input. While this is
+    /// `false`, input is ignored but the camera still coasts and decelerates as usual. This is the
+    /// hook for UI layers to stop the camera from reacting to input meant for the UI, and is
+    /// intended to be set every frame by a system running before `rts_camera_system`. For instance,
+    /// with `bevy_egui`:
+    ///
+    /// ```ignore
+    /// fn block_camera_input(egui: Res<EguiContext>, mut cameras: Query<&mut RtsCamera>) {
+    ///     let ctx = egui.ctx();
+    ///     let wants_input = !ctx.wants_pointer_input() && !ctx.wants_keyboard_input();
+    ///
+    ///     for mut camera in cameras.iter_mut() {
+    ///         camera.wants_input = wants_input;
+    ///     }
+    /// }
+    /// ```
+    pub wants_input: bool,
+    /// Which kinds of player input the camera responds to. Unlike `wants_input`, this is intended
+    /// as configuration, e.g to lock the zoom or disable edge panning.
+    pub controls: CameraControls,
+    /// The time in seconds since the last frame in which any pan, zoom, turn or pitch input
+    /// affected the camera. This is updated by the system and should not be modified directly.
+    /// Rather, read it with [`RtsCamera::idle_secs`].
+    pub idle_secs: f32,
+    /// The magnitudes of the directional pan input, overriding the pan keys. This allows analog
+    /// sources such as pressure sensitive d-pads to drive panning through the same path as the
+    /// keyboard, with the keyboard acceleration scaled by the magnitude. If this is `None`, the
+    /// magnitudes are taken from the pan keys, where a pressed key has a magnitude of 1.
+    pub pan_input: Option<PanMagnitudes>,
+    /// The zoom input as an axis from -1 (zoom in) to 1 (zoom out), overriding the zoom keys. This
+    /// allows analog sources such as a pair of gamepad triggers, or a program, to drive zooming
+    /// through the same path as the keyboard, with the keyboard acceleration scaled by the axis. If
+    /// this is `None`, the axis is taken from the zoom keys.
+    pub zoom_input: Option<f32>,
+    /// The magnitude below which scroll deltas, pan magnitudes and the zoom axis are treated as
+    /// zero, so that controller drift or stray scroll ticks do not count as input. This keeps
+    /// the camera settling and the idle time counting. Key presses always have a magnitude of 1.
+    pub input_deadzone: f32,
+    /// The smoothed turn acceleration per second, when the turn settings' `input_smoothing` is
+    /// enabled. This is updated by the system and should not be modified directly.
+    pub turn_input_rate: f32,
+    /// Whether the camera has been turned freely since it last settled onto the turn settings'
+    /// `snap_on_release` increment. This is updated by the system and should not be modified
+    /// directly.
+    pub turn_released: bool,
+    /// The ground plane, which the camera pans within and which [`RtsCamera::cursor_to_ground`]
+    /// projects onto, relative to the rig if the camera has one. This is the horizontal XZ plane by
+    /// default, but may be tilted. For horizontal ground at another height, use
+    /// [`Plane::horizontal`].
+    pub ground: Plane,
+    /// A stack of temporary settings overrides, of which the top one is used in place of the
+    /// camera's settings components while it is active. See
+    /// [`RtsCamera::push_settings_override`].
+    pub settings_overrides: Vec<SettingsOverride>,
+    /// An offset added to the translation of the rendered camera transform, without affecting the
+    /// camera state. This is used by cosmetic effects such as the [`PanBounds`] edge bump, which
+    /// set it and let it decay back to zero.
+    pub render_offset: Vec3,
+    /// The time constant in seconds with which the rendered camera's focus follows `looking_at`,
+    /// giving the camera a heavy feel. Gameplay queries such as `looking_at` itself are not
+    /// affected. If this is zero, the rendered camera looks at `looking_at` exactly.
+    pub render_smoothing: f32,
+    /// The minimum distance in logical pixels between the target and the edges of the window. When
+    /// the bounds clamp `looking_at` so far from where it was moved to, e.g by following a unit to
+    /// the edge of the map, that the point would appear closer to the edge than this, `looking_at`
+    /// is moved back towards it past the bounds just far enough to keep it in view. Likewise, the
+    /// smoothed focus of `render_smoothing` is pulled along when it lags so far behind that
+    /// `looking_at` would appear closer to the edge than this. If this is zero, neither is limited.
+    pub focus_screen_margin: f32,
+    /// The height above `looking_at` at which the rendered camera aims, e.g to frame units around
+    /// their heads while the target stays at ground level for gameplay queries such as picking.
+    pub focus_height_offset: f32,
+    /// The smoothed focus of the rendered camera, when `render_smoothing` is enabled. This is
+    /// updated by the system and should not be modified directly.
+    pub render_looking_at: Option<Vec3>,
+    /// The smooth move which the camera is currently making, if any. Any input cancels it.
+    pub tween: Option<Tween>,
+    /// An entity which the camera will smoothly move to look at on the next update, as requested by
+    /// [`RtsCamera::look_at_entity_once`]
+    pub focus_entity: Option<Entity>,
+    /// The duration in seconds of smooth moves such as [`RtsCamera::focus_smooth`]
+    pub focus_secs: f32,
+    /// The standard zoom distance which focusing with [`ZoomBehavior::Default`] zooms to
+    pub default_zoom_distance: f32,
+    /// The priority of the scripted move which currently holds the camera, if any. While the
+    /// camera is held, player input is ignored. See [`RtsCamera::acquire`].
+    pub lock: Option<u32>,
+    /// A multiplier applied to the time step of this camera's motion and smooth moves, independent
+    /// of the game clock, e.g for slow motion cinematics. Values below 1 slow the camera down, and
+    /// values above 1 speed it up.
+    pub time_scale: f32,
+    /// The maximum angle in radians by which the rendered camera leans towards the cursor when it
+    /// is at the edge of the screen, giving a subtle parallax-like response to mouse movement. The
+    /// lean eases back to neutral as the cursor returns to the centre. This is purely cosmetic and
+    /// does not affect the camera state. If this is zero, the camera does not lean.
+    pub tilt_toward_cursor: f32,
+    /// The current yaw (x) and pitch (y) in radians of the lean towards the cursor. This is updated
+    /// by the system and should not be modified directly.
+    pub render_tilt: Vec2,
+    /// The time constant in seconds with which the translation of the rendered camera follows that
+    /// of the camera state, e.g to make the camera translate heavily while still rotating crisply.
+    /// Unlike `render_smoothing`, this smooths the whole translation including that from turning
+    /// and zooming. If this is zero, the translation is not smoothed.
+    pub render_pos_smoothing: f32,
+    /// The time constant in seconds with which the rotation of the rendered camera follows that of
+    /// the camera state. If this is zero, the rotation is not smoothed.
+    pub render_rot_smoothing: f32,
+    /// The smoothed translation and rotation of the rendered camera as of the last update. This is
+    /// updated by the system and should not be modified directly.
+    pub render_pose: Option<(Vec3, Quat)>,
+    /// The transform which the system last wrote to the camera entity, including the cosmetic
+    /// tilt, bump and smoothing, so that picking methods such as [`RtsCamera::cursor_ray`] match
+    /// what is on screen. This is updated by the system and should not be modified directly. It is
+    /// `None` until the first update, in which case the transform of the camera state is used.
+    pub rendered_transform: Option<Transform>,
+    /// The world transform of the rig entity which the camera entity is a child of, or the identity
+    /// if it has no parent. The picking and projection methods compose the rendered transform with
+    /// this, so that they work in world space. This is updated by the system and should not be
+    /// modified directly.
+    pub rig_transform: Mat4,
+    /// How much momentum carries over between zooming and panning when input for one stops
+    pub cross_coupling: CrossCoupling,
+    /// Whether there was zoom input and pan input respectively on the last frame, used to detect
+    /// when they stop, e.g for `cross_coupling`. This is updated by the system and should not be
+    /// modified directly.
+    pub input_active: (bool, bool),
 }
 
 impl Default for RtsCamera {
@@ -69,374 +969,4256 @@ impl Default for RtsCamera {
             looking_at: Vec3::zero(),
             rotation: Quat::default(),
             yaw: 0.0,
+            pitch_offset: 0.0,
+            zoom_pitch: None,
             zoom_velocity: 0.0,
             pan_velocity: Vec2::zero(),
             turn_velocity: 0.0,
-            last_scroll_sec: 0.0,
+            last_scroll_sec: f64::NEG_INFINITY,
+            scroll_interval_secs: f64::INFINITY,
+            last_scroll_pan_sec: f64::NEG_INFINITY,
+            scroll_pan_interval_secs: f64::INFINITY,
+            pending_scroll: 0.0,
+            pending_scroll_rate: 0.0,
             zoom_distance: 10.0,
+            camera_distance: 10.0,
+            zoom_range_return: false,
+            last_distance_range: None,
             cursor_scroll_event_reader: EventReader::default(),
+            mouse_motion_event_reader: EventReader::default(),
+            window_focus_event_reader: EventReader::default(),
+            window_focused: true,
+            freeze_on_unfocus: false,
+            last_pan_magnitudes: PanMagnitudes::default(),
+            mouse_input: MouseInput::Cursor,
+            mouse_motion: Vec2::zero(),
+            last_cursor: None,
+            wants_input: true,
+            controls: CameraControls::ALL,
+            idle_secs: 0.0,
+            pan_input: None,
+            zoom_input: None,
+            input_deadzone: 0.0,
+            turn_input_rate: 0.0,
+            turn_released: false,
+            ground: Plane::horizontal(0.0),
+            settings_overrides: Vec::new(),
+            render_offset: Vec3::zero(),
+            render_smoothing: 0.0,
+            focus_screen_margin: 0.0,
+            focus_height_offset: 0.0,
+            render_looking_at: None,
+            tween: None,
+            focus_entity: None,
+            focus_secs: 0.5,
+            default_zoom_distance: 10.0,
+            lock: None,
+            time_scale: 1.0,
+            tilt_toward_cursor: 0.0,
+            render_tilt: Vec2::zero(),
+            render_pos_smoothing: 0.0,
+            render_rot_smoothing: 0.0,
+            render_pose: None,
+            rendered_transform: None,
+            rig_transform: Mat4::identity(),
+            cross_coupling: CrossCoupling::default(),
+            input_active: (false, false),
         }
     }
 }
 
 impl RtsCamera {
-    fn camera_translation(&self) -> Vec3 {
-        self.looking_at + self.rotation * Vec3::new(0.0, 0.0, self.zoom_distance)
+    /// Temporarily override the camera's settings, e.g to slow the camera down during a scripted
+    /// sequence. The override lasts until it is popped with [`RtsCamera::pop_settings_override`],
+    /// at which point the previous settings are used again. Overrides may be nested.
+    pub fn push_settings_override(&mut self, settings: SettingsOverride) {
+        self.settings_overrides.push(settings);
     }
 
-    fn camera_transform(&self) -> Transform {
-        let mat4 = Mat4::from_rotation_translation(self.rotation, self.camera_translation());
-        Transform::from_matrix(mat4)
+    /// Remove the most recently pushed settings override, returning it if there was one.
+    pub fn pop_settings_override(&mut self) -> Option<SettingsOverride> {
+        self.settings_overrides.pop()
+    }
+
+    /// The time in seconds since the last frame in which any pan, zoom, turn or pitch input
+    /// affected the camera, e.g for dimming the HUD when the player is idle. This is reset to zero
+    /// on every frame with input.
+    pub fn idle_secs(&self) -> f32 {
+        self.idle_secs
     }
 
-    fn rotate(&mut self, angle: f32) {
-        self.yaw += angle;
+    /// The pan velocity in world space, within the ground plane.
+    fn world_pan_velocity(&self) -> Vec3 {
+        let (right, ahead) = self.motion_state().pan_basis();
+        right * self.pan_velocity.x + ahead * self.pan_velocity.y
+    }
 
-        if self.yaw > TAU {
-            self.yaw -= TAU;
-        }
+    /// Set the pan velocity from a velocity in world space. Only the X and Z components are used,
+    /// which determine the velocity within the ground plane unless the plane is vertical.
+    fn set_world_pan_velocity(&mut self, velocity: Vec3) {
+        let (right, ahead) = self.motion_state().pan_basis();
+        let determinant = right.x * ahead.z - ahead.x * right.z;
 
-        if self.yaw < 0.0 {
-            self.yaw += TAU;
+        if determinant.abs() <= f32::EPSILON {
+            return;
         }
 
-        let rotation_y = Quat::from_rotation_y(angle);
-        let camera_translation = self.camera_translation();
-        self.looking_at = (rotation_y * (self.looking_at - camera_translation)) + camera_translation;
+        self.pan_velocity = Vec2::new(
+            (velocity.x * ahead.z - ahead.x * velocity.z) / determinant,
+            (right.x * velocity.z - velocity.x * right.z) / determinant,
+        );
     }
 
-    fn tick(
-        &mut self,
-        scroll: Option<f32>,
-        cursor: Vec2,
-        window: &Window,
-        keyboard: &Input<KeyCode>,
-        zoom: &ZoomSettings,
-        pan: &PanSettings,
-        turn: &TurnSettings,
-        time: &Time,
-    ) {
-        let (delta, now) = (time.delta_seconds(), time.seconds_since_startup());
-        let [mut x_decel, mut y_decel, mut turn_decel]: [Deceleration; 3] = Default::default();
-
-        let mut zoom_decel = if (now - self.last_scroll_sec) < SCROLL_TICK_GRACE_SECS {
-            Deceleration { pos: false, neg: false }
+    /// Clamp the target to within the bounds, arresting any pan velocity into them and bumping the
+    /// rendered transform if configured.
+    fn apply_bounds(&mut self, bounds: &PanBounds, delta: f32) {
+        if bounds.bump_decay > 0.0 {
+            self.render_offset *= (-delta / bounds.bump_decay).exp();
         } else {
-            Deceleration { pos: true, neg: true }
-        };
+            self.render_offset = Vec3::zero();
+        }
 
-        if cursor.x < pan.mouse_accel_margin {
-            if cursor.y > window.height() * (1.0 - turn.mouse_turn_margin) {
-                self.turn_velocity += turn.mouse_accel * delta;
-                turn_decel.pos = false;
-            } else {
-                self.pan_velocity.x -= pan.mouse_accel * delta;
-                x_decel.neg = false;
+        // The bounds are in X and Z, so on tilted ground the target is kept at the same height
+        // above the ground at the constrained point
+        let target = Vec2::new(self.looking_at.x, self.looking_at.z);
+        let constrained = bounds.shape.constrain(target);
+        let clamped = Vec3::new(constrained.x, self.looking_at.y, constrained.y);
+        let clamped = self.ground.lift_to_distance(clamped, self.ground.distance_to(self.looking_at));
+
+        if clamped == self.looking_at {
+            return;
+        }
+
+        let velocity = self.world_pan_velocity();
+        let mut arrested = Vec3::zero();
+
+        if let BoundsShape::Rect { .. } = bounds.shape {
+            // Stop each axis separately, so that the camera can slide along the edges and corners
+            if clamped.x != self.looking_at.x && (clamped.x - self.looking_at.x) * velocity.x < 0.0 {
+                arrested.x = velocity.x;
             }
-        } else if cursor.x > window.width() as f32 - pan.mouse_accel_margin {
-            if cursor.y > window.height() * (1.0 - turn.mouse_turn_margin) {
-                self.turn_velocity -= turn.mouse_accel * delta;
-                turn_decel.neg = false;
-            } else {
-                self.pan_velocity.x += pan.mouse_accel * delta;
-                x_decel.pos = false;
+
+            if clamped.z != self.looking_at.z && (clamped.z - self.looking_at.z) * velocity.z < 0.0 {
+                arrested.z = velocity.z;
             }
+        } else {
+            // Stop the component of the velocity out of the region, along the direction in which
+            // the target was pushed back
+            let outward = (self.looking_at - clamped).normalize();
+            arrested = outward * velocity.dot(outward).max(0.0);
         }
 
-        if cursor.y < pan.mouse_accel_margin {
-            self.pan_velocity.y -= pan.mouse_accel * delta;
-            y_decel.neg = false;
-        } else if cursor.y > window.height() as f32 - pan.mouse_accel_margin {
-            self.pan_velocity.y += pan.mouse_accel * delta;
-            y_decel.pos = false;
-        }
+        self.looking_at = clamped;
+        self.set_world_pan_velocity(velocity - arrested);
+        self.render_offset += arrested * bounds.bump;
+    }
 
-        if pan.right_keys.iter().any(|c| keyboard.pressed(*c)) {
-            self.pan_velocity.x += pan.keyboard_accel * delta;
-            x_decel.pos = false;
-        }
+    /// Move the camera away from the target along its line of sight until it is at least the
+    /// terrain's minimum clearance above the terrain under it. This only changes the camera
+    /// distance, which is derived from the zoom distance again on the next tick.
+    fn apply_clearance(&mut self, terrain: &Terrain) {
+        let eye = self.camera_translation();
+        let floor = (terrain.height_at)(Vec2::new(eye.x, eye.z)) + terrain.min_clearance;
+        let rise_per_distance = (self.rotation * Vec3::unit_z()).y;
 
-        if pan.left_keys.iter().any(|c| keyboard.pressed(*c)) {
-            self.pan_velocity.x += -pan.keyboard_accel * delta;
-            x_decel.neg = false;
+        if eye.y < floor && rise_per_distance > 0.0 {
+            self.camera_distance += (floor - eye.y) / rise_per_distance;
         }
+    }
 
-        if pan.up_keys.iter().any(|c| keyboard.pressed(*c)) {
-            self.pan_velocity.y += pan.keyboard_accel * delta;
-            y_decel.pos = false;
-        }
+    /// Ease the cosmetic lean of the rendered camera towards the cursor's offset from the centre
+    /// of the viewport, bounded by `tilt_toward_cursor`.
+    fn update_render_tilt(&mut self, cursor: Option<Vec2>, viewport: Vec2, delta: f32) {
+        let target = match cursor {
+            Some(cursor) if viewport.x > 0.0 && viewport.y > 0.0 => {
+                let offset = cursor / viewport * 2.0 - Vec2::one();
+                offset.max(-Vec2::one()).min(Vec2::one()) * self.tilt_toward_cursor
+            }
+            _ => Vec2::zero(),
+        };
 
-        if pan.down_keys.iter().any(|c| keyboard.pressed(*c)) {
-            self.pan_velocity.y += -pan.keyboard_accel * delta;
-            y_decel.neg = false;
-        }
+        self.render_tilt += (target - self.render_tilt) * (1.0 - (-delta / CURSOR_TILT_EASE_SECS).exp());
+    }
 
-        if turn.right_keys.iter().any(|c| keyboard.pressed(*c)) {
-            self.turn_velocity -= turn.keyboard_accel * delta;
-            turn_decel.neg = false;
-        }
+    /// Ease the rendered translation and rotation towards the given transform according to
+    /// `render_pos_smoothing` and `render_rot_smoothing`, returning the smoothed transform.
+    fn smooth_rendered(&mut self, target: Transform, delta: f32) -> Transform {
+        let ease = |smoothing: f32| 1.0 - (-delta / smoothing).exp();
+        let (mut translation, mut rotation) = (target.translation, target.rotation);
+
+        // Without smoothing, the target is used as it is, as easing all of the way to it is inexact
+        if let Some((last_translation, last_rotation)) = self.render_pose {
+            if self.render_pos_smoothing > 0.0 {
+                translation = last_translation + (translation - last_translation) * ease(self.render_pos_smoothing);
+            }
 
-        if turn.left_keys.iter().any(|c| keyboard.pressed(*c)) {
-            self.turn_velocity += turn.keyboard_accel * delta;
-            turn_decel.pos = false;
+            if self.render_rot_smoothing > 0.0 {
+                rotation = last_rotation.slerp(rotation, ease(self.render_rot_smoothing));
+            }
         }
 
-        if let Some(y) = scroll {
-            if y > 0.0 {
-                zoom_decel.pos = false;
-            } else {
-                zoom_decel.neg = false;
+        self.render_pose = Some((translation, rotation));
+        Transform { translation, rotation, ..target }
+    }
+
+    /// Ease the rendered focus towards `looking_at` according to `render_smoothing`.
+    fn update_render_focus(&mut self, delta: f32) {
+        self.render_looking_at = match self.render_looking_at {
+            Some(focus) if self.render_smoothing > 0.0 => {
+                let factor = 1.0 - (-delta / self.render_smoothing).exp();
+                Some(focus + (self.looking_at - focus) * factor)
             }
+            _ if self.render_smoothing > 0.0 => Some(self.looking_at),
+            _ => None,
+        };
+    }
 
-            self.zoom_velocity -= y * zoom.scroll_accel;
-            self.last_scroll_sec = now;
+    /// How far the given point appears from the centre of the window as seen from the given camera
+    /// state transform, as a multiple of the distance to the inset of `focus_screen_margin`, so
+    /// that it is within the inset if this is at most one. This is `None` if the margin is zero or
+    /// leaves no inset, and infinite if the point is behind the camera.
+    fn margin_excess(&self, transform: &Transform, point: Vec3, window: &Window, camera: &Camera) -> Option<f32> {
+        let margin = self.focus_screen_margin;
+        let half_size = Vec2::new(window.width(), window.height()) / 2.0;
+        let inset = half_size - Vec2::splat(margin);
+
+        if margin <= 0.0 || inset.x <= 0.0 || inset.y <= 0.0 {
+            return None;
         }
 
-        if zoom.zoom_in_keys.iter().any(|c| keyboard.pressed(*c)) {
-            self.zoom_velocity -= zoom.keyboard_accel * delta;
-            zoom_decel.pos = false;
+        let excess = match clip_to_screen(world_to_clip(transform, camera, point), window) {
+            Some(position) => {
+                let offset = (position - half_size).abs() / inset;
+                f32::max(offset.x, offset.y)
+            }
+            None => f32::INFINITY,
+        };
+
+        Some(excess)
+    }
+
+    /// Move `looking_at` from where the bounds clamped it back towards where it was moved to, until
+    /// that point appears at least `focus_screen_margin` from the edges of the window.
+    fn keep_in_margin(&mut self, desired: Vec3, window: &Window, camera: &Camera) {
+        // The screen position of the point does not change exactly in proportion to the distance
+        // from it, so this converges over a few steps
+        for _ in 0..FOCUS_MARGIN_STEPS {
+            let transform = self.transform_looking_at(self.looking_at);
+
+            match self.margin_excess(&transform, desired, window, camera) {
+                Some(excess) if excess > 1.0 => self.looking_at = desired + (self.looking_at - desired) / excess,
+                _ => return,
+            }
         }
+    }
 
-        if zoom.zoom_out_keys.iter().any(|c| keyboard.pressed(*c)) {
-            self.zoom_velocity += zoom.keyboard_accel * delta;
-            zoom_decel.neg = false;
+    /// Pull the smoothed focus towards `looking_at` until the target appears at least
+    /// `focus_screen_margin` from the edges of the window.
+    fn keep_target_in_margin(&mut self, window: &Window, camera: &Camera) {
+        // The screen position of the target does not change exactly in proportion to the lag of the
+        // focus, so this converges over a few steps
+        for _ in 0..FOCUS_MARGIN_STEPS {
+            let focus = match self.render_looking_at {
+                Some(focus) => focus,
+                None => return,
+            };
+
+            match self.margin_excess(&self.camera_transform(), self.looking_at, window, camera) {
+                Some(excess) if excess > 1.0 => {
+                    self.render_looking_at = Some(self.looking_at + (focus - self.looking_at) / excess);
+                }
+                _ => return,
+            }
         }
+    }
+
+    /// The translation of the rendered camera, which looks at the smoothed focus if there is one,
+    /// raised by the focus height offset.
+    fn camera_translation(&self) -> Vec3 {
+        self.camera_transform().translation
+    }
 
-        // Apply zoom/pan deceleration
-        turn_decel.apply(&mut self.turn_velocity, turn.idle_deceleration, delta);
-        zoom_decel.apply(&mut self.zoom_velocity, zoom.idle_deceleration, delta);
-        x_decel.apply(&mut self.pan_velocity.x, pan.idle_deceleration, delta);
-        y_decel.apply(&mut self.pan_velocity.y, pan.idle_deceleration, delta);
+    fn camera_transform(&self) -> Transform {
+        self.transform_looking_at(self.render_looking_at.unwrap_or(self.looking_at))
+    }
+
+    /// The transform of the camera state when looking at the given focus, raised by the focus
+    /// height offset.
+    fn transform_looking_at(&self, focus: Vec3) -> Transform {
+        let looking_at = focus + Vec3::unit_y() * self.focus_height_offset;
+        let translation = MotionState { looking_at, ..self.motion_state() }.translation();
+        Transform::from_matrix(Mat4::from_rotation_translation(self.rotation, translation))
+    }
+
+    /// The world transform of the camera as rendered, which the picking and projection methods use.
+    fn view_transform(&self) -> Transform {
+        let local = self.rendered_transform.unwrap_or_else(|| self.camera_transform());
+        Transform::from_matrix(self.rig_transform * local.compute_matrix())
+    }
+
+    /// The ground plane in world space.
+    fn world_ground(&self) -> Plane {
+        let point = self.rig_transform.transform_point3(self.ground.point);
+        Plane::new(point, self.rig_transform.transform_vector3(self.ground.normal))
+    }
 
-        // Clamp velocity to max
-        if self.pan_velocity.length_squared() > (pan.max_speed * pan.max_speed) {
-            self.pan_velocity = pan.max_speed * self.pan_velocity.normalize();
+    /// The world space ray from the camera as rendered through the given cursor position in the
+    /// window, as an origin and a normalized direction, for picking. The direction points into the
+    /// scene. This is `None` if the window has no area.
+    pub fn cursor_ray(&self, cursor: Vec2, window: &Window, camera: &Camera) -> Option<(Vec3, Vec3)> {
+        let size = Vec2::new(window.width(), window.height());
+
+        if size.x <= 0.0 || size.y <= 0.0 {
+            return None;
         }
 
-        self.zoom_velocity = clamp(self.zoom_velocity, &(-zoom.max_velocity..=zoom.max_velocity));
-        self.turn_velocity = clamp(self.turn_velocity, &(-turn.max_speed..=turn.max_speed));
+        let ndc = cursor / size * 2.0 - Vec2::one();
+        let far = self.ndc_to_world(camera, Vec3::new(ndc.x, ndc.y, 1.0));
 
-        // Apply zoom velocity
-        self.zoom_distance += self.zoom_velocity * delta;
-        self.zoom_distance = clamp(self.zoom_distance, &zoom.distance_range);
+        let origin = self.view_transform().translation;
+        Some((origin, (far - origin).normalize()))
+    }
 
-        if self.zoom_distance == *zoom.distance_range.start() || self.zoom_distance == *zoom.distance_range.end() {
-            self.zoom_velocity = 0.0;
+    /// The position in the window at which the given world space point appears, in the same
+    /// coordinates as the cursor position. This is `None` if the point is behind the camera or the
+    /// window has no area. The position may be outside of the window.
+    pub fn world_to_screen(&self, world: Vec3, window: &Window, camera: &Camera) -> Option<Vec2> {
+        clip_to_screen(self.world_to_clip(camera, world), window)
+    }
+
+    /// Whether the given world space point appears within the window, and is not behind the camera.
+    pub fn is_on_screen(&self, world: Vec3, window: &Window, camera: &Camera) -> bool {
+        let size = Vec2::new(window.width(), window.height());
+
+        let on_screen = |pos: Vec2| pos.x >= 0.0 && pos.y >= 0.0 && pos.x <= size.x && pos.y <= size.y;
+        matches!(self.world_to_screen(world, window, camera), Some(pos) if on_screen(pos))
+    }
+
+    /// The position in the window at which the given world space point appears, moved onto the edge
+    /// of the window towards the point if it is off screen, e.g for indicators pointing towards
+    /// off screen pings. Points behind the camera are placed on the edge in their direction from the
+    /// centre of the view. This is `None` if the window has no area.
+    pub fn screen_edge_position(&self, world: Vec3, window: &Window, camera: &Camera) -> Option<Vec2> {
+        let size = Vec2::new(window.width(), window.height());
+
+        if size.x <= 0.0 || size.y <= 0.0 {
+            return None;
         }
 
-        // Apply turn velocity
-        self.rotate(self.turn_velocity * delta);
-        self.yaw = clamp(self.yaw, &turn.yaw_range);
+        // Dividing by the magnitude of w keeps the direction of points behind the camera
+        let clip = self.world_to_clip(camera, world);
+        let mut ndc = Vec2::new(clip.x, clip.y) / clip.w.abs().max(f32::EPSILON);
+        let extent = f32::max(ndc.x.abs(), ndc.y.abs());
 
-        // Rotate camera angle depending on zoom (pitch) and yaw
-        let pitch = lerp_in_zone(self.zoom_distance, &zoom.angle_change_zone, &zoom.angle_range);
-        self.rotation = Quat::from_rotation_ypr(self.yaw, -pitch, 0.0);
+        if clip.w <= 0.0 || extent > 1.0 {
+            ndc /= extent.max(f32::EPSILON);
+        }
 
-        // Apply pan velocity, taking into account the rotation of the camera
-        let forward = Quat::from_rotation_y(self.yaw);
-        let distance_factor = lerp_in_zone(self.zoom_distance, &zoom.angle_range, &pan.pan_speed_zoom_factor_range);
-        self.looking_at += forward * (Vec3::unit_x() * self.pan_velocity.x * delta) * distance_factor;
-        self.looking_at += forward * (-Vec3::unit_z() * self.pan_velocity.y * delta) * distance_factor;
+        Some((ndc + Vec2::one()) / 2.0 * size)
     }
-}
 
-#[derive(Clone, PartialEq, Debug)]
-pub struct ZoomSettings {
-    /// The minimum and maximum angle in radians from the target
-    pub angle_range: RangeInclusive<f32>,
+    /// Transform a world space point into the clip space of the given camera, using the camera's
+    /// rendered transform.
+    fn world_to_clip(&self, camera: &Camera, world: Vec3) -> Vec4 {
+        world_to_clip(&self.view_transform(), camera, world)
+    }
 
-    /// At the minimum distance, the angle to the target will be equal to `min_angle`, and vice versa.
-    /// In other words, at these points the camera will only zoom in or out rather than also changing
-    /// its angle - the angle only changes within this distance zone.
-    pub angle_change_zone: RangeInclusive<f32>,
+    /// The world space corners of the view frustum of the given camera, in the order bottom left,
+    /// bottom right, top right and top left on the near plane, followed by the same on the far
+    /// plane.
+    pub fn frustum_corners(&self, camera: &Camera) -> [Vec3; 8] {
+        let mut corners = [Vec3::zero(); 8];
+        let ndc_corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
 
-    /// The minimum and maximum distance from the target
-    pub distance_range: RangeInclusive<f32>,
+        for (i, (x, y)) in ndc_corners.iter().enumerate() {
+            corners[i] = self.ndc_to_world(camera, Vec3::new(*x, *y, 0.0));
+            corners[i + 4] = self.ndc_to_world(camera, Vec3::new(*x, *y, 1.0));
+        }
 
-    /// The current velocity at which the camera is zooming in or out
-    pub velocity: f32,
-    /// The maximum velocity at which the camera can zoom in or out
-    pub max_velocity: f32,
-    /// The acceleration which the scroll wheel applies to the camera zoom while scrolling. Note
-    /// that because of the discrete way in which scroll events are sent to the application,
-    /// the delta time is *not* multiplied to the scroll accel value before it is added to the
-    /// velocity. Therefore, this acts as the change in velocity per line or pixel scrolled, rather
-    /// than the acceleration applied over a second of input.
-    pub scroll_accel: f32,
-    /// The acceleration which the keyboard applies to the camera zoom while scrolling
-    pub keyboard_accel: f32,
-    /// The deceleration of the camera zoom while nothing is causing it to zoom in or out
-    pub idle_deceleration: f32,
+        corners
+    }
 
-    /// Keys which will cause the camera to zoom in
-    pub zoom_in_keys: Cow<'static, [KeyCode]>,
-    /// Keys which will cause the camera to zoom out
-    pub zoom_out_keys: Cow<'static, [KeyCode]>,
-}
+    /// Transform a point in the normalized device coordinates of the given camera into world
+    /// space, using the camera's rendered transform.
+    fn ndc_to_world(&self, camera: &Camera, ndc: Vec3) -> Vec3 {
+        let ndc_to_world = self.view_transform().compute_matrix() * camera.projection_matrix.inverse();
+        let world = ndc_to_world * ndc.extend(1.0);
+        Vec3::new(world.x, world.y, world.z) / world.w
+    }
 
-impl ZoomSettings {
-    pub const fn new() -> Self {
-        ZoomSettings {
-            angle_range: 0.5705693..=1.1637539,
-            angle_change_zone: 5.0..=100.0,
-            distance_range: 5.0..=100.0,
-            velocity: 0.0,
-            max_velocity: 5.0,
-            scroll_accel: 5.0,
-            keyboard_accel: 5.0,
-            idle_deceleration: 5.0,
-            zoom_in_keys: Cow::Borrowed(&[KeyCode::Equals, KeyCode::NumpadAdd]),
-            zoom_out_keys: Cow::Borrowed(&[KeyCode::NumpadSubtract, KeyCode::Minus]),
+    /// The point on the ground plane under the given cursor position in the window, or `None` if the
+    /// cursor is not over the ground.
+    pub fn cursor_to_ground(&self, cursor: Vec2, window: &Window, camera: &Camera) -> Option<Vec3> {
+        let (origin, direction) = self.cursor_ray(cursor, window, camera)?;
+        self.world_ground().ray_intersection(origin, direction)
+    }
+
+    /// The point on the ground plane at the centre of the view of the given camera. This is usually
+    /// `looking_at`, but differs from it while the rendered focus lags behind, or with a focus
+    /// height offset. It is `None` if the centre of the view is not over the ground, such as when
+    /// the camera is pitched up above the horizon.
+    pub fn screen_center_ground(&self, camera: &Camera) -> Option<Vec3> {
+        let origin = self.view_transform().translation;
+        let far = self.ndc_to_world(camera, Vec3::new(0.0, 0.0, 1.0));
+        self.world_ground().ray_intersection(origin, (far - origin).normalize())
+    }
+
+    /// Move the target by the distance on the ground which corresponds to the given offset in
+    /// logical pixels on the screen at the centre of the view, e.g for a button panning one screen
+    /// to the left or for stepping the camera with the keyboard. The distance is measured on the
+    /// plane through the target parallel to the ground. Returns whether the camera moved, which it
+    /// does not if the offset point is not over that plane, such as when it is above the horizon.
+    pub fn nudge_screen(&mut self, delta: Vec2, window: &Window, camera: &Camera) -> bool {
+        let centre = Vec2::new(window.width(), window.height()) / 2.0;
+        let plane = Plane::new(self.rig_transform.transform_point3(self.looking_at), self.world_ground().normal);
+        let project = |cursor| {
+            let (origin, direction) = self.cursor_ray(cursor, window, camera)?;
+            plane.ray_intersection(origin, direction)
+        };
+
+        match (project(centre), project(centre + delta)) {
+            (Some(from), Some(to)) => {
+                self.looking_at += self.rig_transform.inverse().transform_vector3(to - from);
+                true
+            }
+            _ => false,
         }
     }
-}
 
-impl Default for ZoomSettings {
-    fn default() -> Self { ZoomSettings::new() }
-}
+    /// The footprint on the ground plane of the screen space rectangle between the given corners in
+    /// the window, e.g a selection drag box, in the order of the screen corners `min`, `(max.x,
+    /// min.y)`, `max` and `(min.x, max.y)`. This is `None` if any of the corners is not over the
+    /// ground, such as when the rectangle extends above the horizon.
+    pub fn screen_rect_to_ground(
+        &self,
+        min: Vec2,
+        max: Vec2,
+        window: &Window,
+        camera: &Camera,
+    ) -> Option<[Vec3; 4]> {
+        let corner = |x, y| self.cursor_to_ground(Vec2::new(x, y), window, camera);
 
-#[derive(Clone, PartialEq, Debug)]
-pub struct PanSettings {
-    /// The acceleration which the mouse applies to the camera's panning motion.
-    pub mouse_accel: f32,
-    /// The minimum distance from the edge of the window the mouse must be in order for the camera
-    /// to begin panning.
-    pub mouse_accel_margin: f32,
-    /// The acceleration that they keyboard applies to the camera's panning motion
-    pub keyboard_accel: f32,
-    /// The maximum velocity at which the camera may pan
-    pub max_speed: f32,
-    /// The deceleration of the panning while nothing is accelerating it in a certain direction
-    pub idle_deceleration: f32,
+        Some([
+            corner(min.x, min.y)?,
+            corner(max.x, min.y)?,
+            corner(max.x, max.y)?,
+            corner(min.x, max.y)?,
+        ])
+    }
 
-    /// The effect of zoom distance on pan speed. This can be set to make panning faster when more
-    /// zoomed out. The start value of this range is the factor at the minimum zoom level, and the
-    /// end is the factor at the maximum zoom level. The factor will be linearly interpolated
-    /// according to the zoom distance.
-    pub pan_speed_zoom_factor_range: RangeInclusive<f32>,
+    /// The point on the horizontal plane at the given height under the given cursor position in
+    /// the window, e.g for picking against a build layer, or `None` if the cursor is not over the
+    /// plane.
+    pub fn cursor_to_plane(&self, cursor: Vec2, window: &Window, camera: &Camera, y: f32) -> Option<Vec3> {
+        let (origin, direction) = self.cursor_ray(cursor, window, camera)?;
+        Plane::horizontal(y).ray_intersection(origin, direction)
+    }
 
-    /// The keys which will cause the camera to pan left
-    pub left_keys: Cow<'static, [KeyCode]>,
-    /// The keys which will cause the camera to pan right
-    pub right_keys: Cow<'static, [KeyCode]>,
-    /// The keys which will cause the camera to pan up
-    pub up_keys: Cow<'static, [KeyCode]>,
-    /// The keys which will cause the camera to pan down
-    pub down_keys: Cow<'static, [KeyCode]>,
-}
+    /// Take hold of the camera for a scripted move (e.g. an alert or tutorial) at the given
+    /// priority, which must be greater than [`PLAYER_PRIORITY`]. While the camera is held, player
+    /// input and moves at a lower priority are ignored, and any such move in progress is cancelled.
+    /// Moves such as [`RtsCamera::tween_to`] and the focus methods are made at the player's
+    /// priority, so the holder moves the camera with [`RtsCamera::start_tween`] and a tween at the
+    /// held priority. This returns whether the camera was acquired, which is the case unless it is
+    /// already held at a higher priority. Callers should only move the camera if it was.
+    pub fn acquire(&mut self, priority: u32) -> bool {
+        if matches!(self.lock, Some(held) if held > priority) {
+            return false;
+        }
 
-impl PanSettings {
-    pub const fn new() -> Self {
-        PanSettings {
-            mouse_accel: 15.0,
-            mouse_accel_margin: 10.0,
-            keyboard_accel: 5.0,
-            max_speed: 5.0,
-            idle_deceleration: 17.5,
-            pan_speed_zoom_factor_range: 1.0..=2.0,
-            left_keys: Cow::Borrowed(&[KeyCode::Left, KeyCode::A]),
-            right_keys: Cow::Borrowed(&[KeyCode::Right, KeyCode::D]),
-            up_keys: Cow::Borrowed(&[KeyCode::Up, KeyCode::W]),
-            down_keys: Cow::Borrowed(&[KeyCode::Down, KeyCode::S]),
+        self.lock = Some(priority);
+        true
+    }
+
+    /// Release the camera after a scripted move, so that player input and moves of any priority
+    /// are accepted again.
+    pub fn release(&mut self) {
+        self.lock = None;
+    }
+
+    /// Whether a move at the given priority may currently control the camera.
+    pub fn accepts(&self, priority: u32) -> bool {
+        !matches!(self.lock, Some(held) if priority < held)
+    }
+
+    /// Capture the runtime state of the camera, including its velocities, for rollback or
+    /// resimulation. Transient input state such as the scroll event reader is not included.
+    pub fn snapshot(&self) -> RtsCameraSnapshot {
+        RtsCameraSnapshot {
+            looking_at: self.looking_at,
+            rotation: self.rotation,
+            yaw: self.yaw,
+            pitch_offset: self.pitch_offset,
+            zoom_pitch: self.zoom_pitch,
+            zoom_distance: self.zoom_distance,
+            pan_velocity: self.pan_velocity,
+            zoom_velocity: self.zoom_velocity,
+            turn_velocity: self.turn_velocity,
+            last_scroll_sec: Some(self.last_scroll_sec).filter(|secs| secs.is_finite()),
+            scroll_interval_secs: Some(self.scroll_interval_secs).filter(|secs| secs.is_finite()),
+            last_scroll_pan_sec: Some(self.last_scroll_pan_sec).filter(|secs| secs.is_finite()),
+            scroll_pan_interval_secs: Some(self.scroll_pan_interval_secs).filter(|secs| secs.is_finite()),
+            pending_scroll: self.pending_scroll,
+            pending_scroll_rate: self.pending_scroll_rate,
+            turn_input_rate: self.turn_input_rate,
         }
     }
-}
 
-impl Default for PanSettings {
-    fn default() -> Self { PanSettings::new() }
-}
+    /// Restore the runtime state of the camera from a snapshot. Any smooth move is cancelled, and
+    /// the camera distance is derived from the restored state on the next tick.
+    pub fn restore(&mut self, snapshot: &RtsCameraSnapshot) {
+        self.looking_at = snapshot.looking_at;
+        self.rotation = snapshot.rotation;
+        self.yaw = snapshot.yaw;
+        self.pitch_offset = snapshot.pitch_offset;
+        self.zoom_pitch = snapshot.zoom_pitch;
+        self.zoom_distance = snapshot.zoom_distance;
+        self.pan_velocity = snapshot.pan_velocity;
+        self.zoom_velocity = snapshot.zoom_velocity;
+        self.turn_velocity = snapshot.turn_velocity;
+        self.last_scroll_sec = snapshot.last_scroll_sec.unwrap_or(f64::NEG_INFINITY);
+        self.scroll_interval_secs = snapshot.scroll_interval_secs.unwrap_or(f64::INFINITY);
+        self.last_scroll_pan_sec = snapshot.last_scroll_pan_sec.unwrap_or(f64::NEG_INFINITY);
+        self.scroll_pan_interval_secs = snapshot.scroll_pan_interval_secs.unwrap_or(f64::INFINITY);
+        self.pending_scroll = snapshot.pending_scroll;
+        self.pending_scroll_rate = snapshot.pending_scroll_rate;
+        self.turn_input_rate = snapshot.turn_input_rate;
+        self.tween = None;
+    }
 
-pub struct TurnSettings {
-    /// The distance that the mouse must be from the top of the screen before it will start turning,
-    /// provided that it is within the pan settings margin. This is measured as a ratio of the height
-    /// dimension of the screen.
-    pub mouse_turn_margin: f32,
-    /// The range of yaw that the camera may turn, in radians
-    pub yaw_range: RangeInclusive<f32>,
-    /// The acceleration which the mouse applies to the camera's turning velocity (measured in
-    /// radians per seconds squared)
-    pub mouse_accel: f32,
-    /// The acceleration which the keyboard applies to the camera's turning velocity (measured in
-    /// radians per seconds squared)
-    pub keyboard_accel: f32,
-    pub max_speed: f32,
-    pub idle_deceleration: f32,
-    /// The keys which will cause the camera to turn left
-    pub left_keys: Cow<'static, [KeyCode]>,
-    /// The keys which will cause the camera to turn right
-    pub right_keys: Cow<'static, [KeyCode]>,
-}
+    /// The current pose of the camera.
+    pub fn state(&self) -> CameraState {
+        CameraState {
+            looking_at: self.looking_at,
+            yaw: self.yaw,
+            zoom_distance: self.zoom_distance,
+        }
+    }
 
-impl TurnSettings {
-    pub const fn new() -> Self {
-        TurnSettings {
-            mouse_turn_margin: 0.25,
-            yaw_range: 0.0..=TAU,
-            mouse_accel: 0.3,
-            keyboard_accel: 1.8,
-            max_speed: 1.5,
-            idle_deceleration: 5.0,
-            left_keys: Cow::Borrowed(&[KeyCode::Q]),
-            right_keys: Cow::Borrowed(&[KeyCode::E]),
+    /// Smoothly move the camera to the given pose over `duration` seconds, at [`PLAYER_PRIORITY`].
+    /// As with the other moves, e.g the focus methods, this is ignored while the camera is held by
+    /// a scripted move. Scripted moves use [`RtsCamera::start_tween`] instead.
+    pub fn tween_to(&mut self, target: CameraState, duration: f32) {
+        self.start_tween(Tween::new(self.state(), target, duration));
+    }
+
+    /// Start the given smooth move, unless the camera is held at a higher priority than that of the
+    /// move. This returns whether the move was started.
+    pub fn start_tween(&mut self, tween: Tween) -> bool {
+        if !self.accepts(tween.priority) {
+            return false;
+        }
+
+        self.tween = Some(tween);
+        true
+    }
+
+    /// Immediately move the camera to look at the given point, stopping any panning, and change
+    /// the zoom distance according to `zoom`. This is ignored while the camera is held by a
+    /// scripted move.
+    pub fn focus_on(&mut self, point: Vec3, zoom: ZoomBehavior) {
+        if !self.accepts(PLAYER_PRIORITY) {
+            return;
+        }
+
+        self.tween = None;
+        self.looking_at = point;
+        self.pan_velocity = Vec2::zero();
+
+        if let Some(distance) = self.zoom_distance_for(zoom) {
+            self.zoom_distance = distance;
+            self.zoom_velocity = 0.0;
         }
     }
-}
 
-impl Default for TurnSettings {
-    fn default() -> Self { TurnSettings::new() }
-}
+    /// Smoothly move the camera to look at the given point over `focus_secs` seconds, zooming
+    /// along the way according to `zoom`.
+    pub fn focus_smooth(&mut self, point: Vec3, zoom: ZoomBehavior) {
+        let zoom_distance = self.zoom_distance_for(zoom).unwrap_or(self.zoom_distance);
+        let target = CameraState { looking_at: point, zoom_distance, ..self.state() };
+        self.tween_to(target, self.focus_secs);
+    }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-struct Deceleration {
-    /// Decelerate against motion in the positive direction
-    pos: bool,
-    /// Decelerate against motion in the negative direction
-    neg: bool,
-}
+    /// The zoom distance to move to for the given zoom behaviour, or `None` to keep it.
+    fn zoom_distance_for(&self, zoom: ZoomBehavior) -> Option<f32> {
+        match zoom {
+            ZoomBehavior::Keep => None,
+            ZoomBehavior::SetTo(distance) => Some(distance),
+            ZoomBehavior::Default => Some(self.default_zoom_distance),
+        }
+    }
 
-impl Default for Deceleration {
-    fn default() -> Self {
-        Deceleration { pos: true, neg: true }
+    /// Smoothly move the camera to look at the current position of the given entity once, without
+    /// following it afterwards as [`Follow`] does, e.g for a "jump to hero" hotkey. The position is
+    /// read from the entity's `GlobalTransform` on the next update of the camera. If the entity
+    /// does not exist then, a warning is logged and the camera does not move.
+    pub fn look_at_entity_once(&mut self, target: Entity) {
+        self.focus_entity = Some(target);
     }
-}
 
-impl Deceleration {
-    fn apply(&self, velocity: &mut f32, magnitude: f32, delta: f32) {
-        if *velocity == 0.0 {
+    /// Smoothly move the camera to look at the centroid of the given points, e.g the selected
+    /// units. This does nothing if there are no points.
+    pub fn focus_on_centroid(&mut self, points: &[Vec3]) {
+        if let Some(centroid) = centroid(points) {
+            self.focus_smooth(centroid, ZoomBehavior::Keep);
+        }
+    }
+
+    /// Smoothly move the camera to look at the centroid of the given points, also zooming so that
+    /// all of them fit within the view of the given camera. The zoom distance is subject to the
+    /// zoom settings' distance range, and a single point or a tight cluster is framed as if it were
+    /// spread over [`MIN_FRAME_RADIUS`]. This does nothing if there are no points.
+    pub fn focus_on_centroid_framed(&mut self, points: &[Vec3], camera: &Camera, zoom: &ZoomSettings) {
+        let centroid = match centroid(points) {
+            Some(centroid) => centroid,
+            None => return,
+        };
+
+        let radius = points.iter().map(|p| (*p - centroid).length()).fold(MIN_FRAME_RADIUS, f32::max);
+        let zoom_distance = self.distance_to_frame(radius, camera, zoom);
+
+        let target = CameraState { looking_at: centroid, yaw: self.yaw, zoom_distance };
+        self.tween_to(target, self.focus_secs);
+    }
+
+    /// The zoom distance at which a sphere of the given radius around the target fits within the
+    /// view of the given camera along its narrower axis, clamped to the zoom settings' distance
+    /// range. Together with [`RtsCamera::tween_to`], this can be used to frame a selection.
+    pub fn distance_to_frame(&self, radius: f32, camera: &Camera, zoom: &ZoomSettings) -> f32 {
+        clamp(framing_distance(radius, camera), &zoom.distance_range)
+    }
+
+    /// Smoothly move the camera to look at the ground under the centre of the given world space
+    /// axis-aligned bounding box, zooming so that the box fits within the view of the given camera,
+    /// e.g to frame a building or a region. The zoom distance is subject to the zoom settings'
+    /// distance range.
+    pub fn frame_aabb(&mut self, min: Vec3, max: Vec3, camera: &Camera, zoom: &ZoomSettings) {
+        // The box is in world space, but the point looked at is relative to the camera's rig
+        let centre = (min + max) / 2.0;
+        let looking_at = self.ground.project_point(self.rig_transform.inverse().transform_point3(centre));
+        let world_looking_at = self.rig_transform.transform_point3(looking_at);
+
+        // The radius of the sphere around the point looked at which contains the whole box
+        let half_extents = (max - min) / 2.0;
+        let height = f32::max((max.y - world_looking_at.y).abs(), (min.y - world_looking_at.y).abs());
+        let radius = Vec3::new(half_extents.x, height, half_extents.z).length();
+
+        let target = CameraState {
+            looking_at,
+            yaw: self.yaw,
+            zoom_distance: self.distance_to_frame(radius, camera, zoom),
+        };
+
+        self.tween_to(target, self.focus_secs);
+    }
+
+    /// The signed angle in radians by which the camera would turn to face the given yaw the short
+    /// way around, where positive angles turn left.
+    pub fn yaw_to(&self, target_yaw: f32) -> f32 {
+        shortest_yaw_delta(self.yaw, target_yaw)
+    }
+
+    /// Smoothly turn the camera to face the given yaw at the turn settings' maximum speed. The yaw
+    /// is first constrained to the turn settings' yaw sectors or range, and the camera turns the
+    /// short way around unless that would pass through yaw which it may not turn to.
+    pub fn turn_toward(&mut self, target_yaw: f32, turn: &TurnSettings) {
+        let motion = turn.motion();
+        let yaw = constrain_yaw(target_yaw, &motion);
+        let yaw_delta = allowed_yaw_delta(self.yaw, yaw, &motion);
+        let duration = if turn.max_speed > 0.0 { yaw_delta.abs() / turn.max_speed } else { 0.0 };
+
+        let tween = Tween::new(self.state(), CameraState { yaw, ..self.state() }, duration);
+        if self.start_tween(tween.with_yaw_delta(yaw_delta)) {
+            self.turn_velocity = 0.0;
+        }
+    }
+
+    /// Smoothly turn the camera to face the given point from where it is looking, without moving
+    /// the point that it is looking at, as with [`RtsCamera::turn_toward`]. Only the horizontal
+    /// direction to the point is considered, and nothing happens if the point is directly above or
+    /// below the point looked at.
+    pub fn face_point(&mut self, point: Vec3, turn: &TurnSettings) {
+        let direction = point - self.looking_at;
+
+        if direction.x == 0.0 && direction.z == 0.0 {
             return;
         }
 
-        let signum = if self.pos && self.neg {
-            -velocity.signum()
-        } else if self.pos {
-            -1.0
-        } else if self.neg {
-            1.0
-        } else {
-            return; // no deceleration required
+        // A yaw of zero faces along -Z, and positive yaws turn towards -X
+        self.turn_toward(f32::atan2(-direction.x, -direction.z), turn);
+    }
+
+    /// Whether the camera is zoomed in as far as the zoom settings allow, e.g to gray out a zoom
+    /// in button.
+    pub fn at_zoom_min(&self, zoom: &ZoomSettings) -> bool {
+        self.zoom_distance <= zoom.distance_range.start() + LIMIT_EPSILON
+    }
+
+    /// Whether the camera is zoomed out as far as the zoom settings allow.
+    pub fn at_zoom_max(&self, zoom: &ZoomSettings) -> bool {
+        self.zoom_distance >= zoom.distance_range.end() - LIMIT_EPSILON
+    }
+
+    /// Whether the camera is at the lower end of the yaw it may turn to. With `yaw_sectors`, this is
+    /// the start of the sector which the camera is in. If the yaw range covers the whole circle,
+    /// there is no limit and this is always `false`.
+    pub fn at_yaw_min(&self, turn: &TurnSettings) -> bool {
+        matches!(self.yaw_limits(turn), Some(range) if (self.yaw - range.start()).abs() <= LIMIT_EPSILON)
+    }
+
+    /// Whether the camera is at the upper end of the yaw it may turn to. See
+    /// [`RtsCamera::at_yaw_min`].
+    pub fn at_yaw_max(&self, turn: &TurnSettings) -> bool {
+        matches!(self.yaw_limits(turn), Some(range) if (self.yaw - range.end()).abs() <= LIMIT_EPSILON)
+    }
+
+    /// The range of yaw which currently limits the camera's turning, if any.
+    fn yaw_limits(&self, turn: &TurnSettings) -> Option<RangeInclusive<f32>> {
+        let range = match &turn.yaw_sectors {
+            Some(sectors) => sectors
+                .iter()
+                .find(|sector| {
+                    (sector.start() - LIMIT_EPSILON..=sector.end() + LIMIT_EPSILON).contains(&self.yaw)
+                })?
+                .clone(),
+            None => turn.yaw_range.clone(),
         };
 
-        let max_decel = magnitude * delta;
-        let decel_magnitude = f32::min(max_decel.abs(), velocity.abs());
+        if range.end() - range.start() >= TAU - LIMIT_EPSILON {
+            None
+        } else {
+            Some(range)
+        }
+    }
+
+    /// The limits on the speed of the camera currently in effect, given its settings, e.g for a
+    /// tuning overlay. These take into account the active settings override, the effect of the zoom
+    /// distance on the pan speed, and the camera's time scale.
+    pub fn effective_limits(&self, zoom: &ZoomSettings, pan: &PanSettings, turn: &TurnSettings) -> EffectiveLimits {
+        let overrides = self.settings_overrides.last();
+        let zoom = overrides.and_then(|o| o.zoom.as_ref()).unwrap_or(zoom);
+        let pan = overrides.and_then(|o| o.pan.as_ref()).unwrap_or(pan);
+        let turn = overrides.and_then(|o| o.turn.as_ref()).unwrap_or(turn);
+        let pan_factor = pan.speed_zoom_factor(self.zoom_distance, zoom);
 
-        *velocity += decel_magnitude * signum;
+        EffectiveLimits {
+            max_pan_speed: pan.max_speed * pan_factor * self.time_scale,
+            max_zoom_velocity: zoom.max_velocity * self.time_scale,
+            max_turn_speed: turn.max_speed * self.time_scale,
+        }
     }
-}
 
-#[must_use = "clamp returns the new value and does not modify the original"]
-fn clamp(x: f32, range: &RangeInclusive<f32>) -> f32 {
-    if x > *range.end() {
-        *range.end()
-    } else if x < *range.start() {
-        *range.start()
-    } else {
-        x
+    /// The position, orientation and velocities of the camera, as used by the motion model.
+    pub fn motion_state(&self) -> MotionState {
+        MotionState {
+            looking_at: self.looking_at,
+            rotation: self.rotation,
+            yaw: self.yaw,
+            pitch_offset: self.pitch_offset,
+            zoom_pitch: self.zoom_pitch,
+            zoom_distance: self.zoom_distance,
+            camera_distance: self.camera_distance,
+            zoom_range_return: self.zoom_range_return,
+            pan_velocity: self.pan_velocity,
+            zoom_velocity: self.zoom_velocity,
+            turn_velocity: self.turn_velocity,
+            ground: self.ground,
+        }
     }
-}
 
-#[must_use = "lerp_in_zone returns the new value and does not modify the original"]
-fn lerp_in_zone(val: f32, zone: &RangeInclusive<f32>, values: &RangeInclusive<f32>) -> f32 {
-    let in_zone = clamp(val, zone);
-    let normalised = (in_zone - *zone.start()) / (*zone.end() - *zone.start());
-    normalised * (values.end() - values.start()) + values.start()
+    /// Set the position, orientation and velocities of the camera from a motion model state.
+    pub fn set_motion_state(&mut self, state: MotionState) {
+        self.looking_at = state.looking_at;
+        self.rotation = state.rotation;
+        self.yaw = state.yaw;
+        self.pitch_offset = state.pitch_offset;
+        self.zoom_pitch = state.zoom_pitch;
+        self.zoom_distance = state.zoom_distance;
+        self.camera_distance = state.camera_distance;
+        self.zoom_range_return = state.zoom_range_return;
+        self.pan_velocity = state.pan_velocity;
+        self.zoom_velocity = state.zoom_velocity;
+        self.turn_velocity = state.turn_velocity;
+    }
+
+    /// Gather this frame's input into an intent for the motion model.
+    #[allow(clippy::too_many_arguments)]
+    fn read_intent(
+        &mut self,
+        scroll: Option<Vec2>,
+        cursor: Option<Vec2>,
+        viewport: Vec2,
+        keyboard: &Input<KeyCode>,
+        settings: &CameraSettings,
+        delta: f32,
+        now: f64,
+    ) -> Intent {
+        let CameraSettings { zoom, pan, turn, pitch } = *settings;
+        let mut intent = Intent::default();
+        let cursor = cursor.filter(|_| self.mouse_input == MouseInput::Cursor);
+        let cursor_delta = match (cursor, self.last_cursor) {
+            (Some(cursor), Some(last)) => cursor - last,
+            _ => Vec2::zero(),
+        };
+        self.last_cursor = cursor;
+
+        if (now - self.last_scroll_sec) < zoom.scroll_grace(self.scroll_interval_secs) {
+            intent.zoom_decel = Deceleration { pos: false, neg: false };
+        }
+
+        if (now - self.last_scroll_pan_sec) < zoom.scroll_grace(self.scroll_pan_interval_secs) {
+            intent.pan_x_decel = Deceleration { pos: false, neg: false };
+        }
+
+        if !self.wants_input || !self.accepts(PLAYER_PRIORITY) {
+            self.last_pan_magnitudes = PanMagnitudes::default();
+            return intent;
+        }
+
+        let controls = self.controls;
+        let edge_pan = controls.contains(CameraControls::PAN | CameraControls::EDGE_PAN);
+        let pan_chord = all_pressed(keyboard, &pan.modifier_keys);
+        let zoom_chord = all_pressed(keyboard, &zoom.modifier_keys);
+        let turn_chord = all_pressed(keyboard, &turn.modifier_keys);
+        let pitch_chord = all_pressed(keyboard, &pitch.modifier_keys);
+        let mouse_turn = controls.contains(CameraControls::TURN | CameraControls::MOUSE_TURN) && turn_chord;
+
+        // Pan acceleration from the mouse at the screen edges and from the keyboard, per axis
+        let [mut edge_x, mut edge_y, mut key_x, mut key_y]: [AxisInput; 4] = Default::default();
+        // Turn acceleration from all sources, where turning left is positive
+        let mut turn_input = AxisInput::default();
+
+        if let Some(cursor) = cursor {
+            let margin = pan.mouse_accel_margin;
+            // The acceleration given how far into the margin the cursor is, shaped by the response curve
+            let edge_accel = |depth: f32| pan.mouse_accel * pan.edge_response.apply(depth / margin) * delta;
+
+            // How far into the pan margins the cursor is, which is positive if it is within them
+            let (left, right) = (margin - cursor.x, cursor.x - (viewport.x - margin));
+            let (bottom, top) = (margin - cursor.y, cursor.y - (viewport.y - margin));
+            let turn_edge_depth = match turn.mouse_turn_edge {
+                VerticalEdge::Top => top,
+                VerticalEdge::Bottom => bottom,
+            };
+
+            // In the corners at the turn edge, the cursor could both turn and pan vertically
+            let in_turn_margin = mouse_turn && turn.in_mouse_turn_margin(cursor, viewport);
+            let in_corner = in_turn_margin && left.max(right) > 0.0 && turn_edge_depth > 0.0;
+            let (turn_weight, pan_weight) = if in_corner {
+                turn.corner_policy.weights(left.max(right), turn_edge_depth)
+            } else {
+                (1.0, 1.0)
+            };
+            let turns = in_turn_margin && turn_weight > 0.0;
+
+            // Whether the cursor may pan towards an edge, given how far it moved towards it this frame
+            let outward = |motion: f32| !pan.edge_pan_requires_outward_motion || motion > 0.0;
+
+            if left > 0.0 {
+                if turns {
+                    turn_input.pos += turn.mouse_accel * turn_weight * delta;
+                } else if outward(-cursor_delta.x) {
+                    edge_x.neg += edge_accel(left);
+                }
+            } else if right > 0.0 {
+                if turns {
+                    turn_input.neg += turn.mouse_accel * turn_weight * delta;
+                } else if outward(cursor_delta.x) {
+                    edge_x.pos += edge_accel(right);
+                }
+            }
+
+            if bottom > 0.0 && outward(-cursor_delta.y) {
+                edge_y.neg += edge_accel(bottom) * pan_weight;
+            } else if top > 0.0 && outward(cursor_delta.y) {
+                edge_y.pos += edge_accel(top) * pan_weight;
+            }
+        }
+
+        if !edge_pan {
+            edge_x = AxisInput::default();
+            edge_y = AxisInput::default();
+        }
+
+        let deadzone = self.input_deadzone;
+        let pan_magnitudes = if controls.contains(CameraControls::PAN) {
+            let magnitudes = match self.pan_input {
+                Some(magnitudes) => magnitudes,
+                None if pan_chord => PanMagnitudes::from_keys(keyboard, pan),
+                None => PanMagnitudes::default(),
+            };
+
+            PanMagnitudes {
+                left: apply_deadzone(magnitudes.left, deadzone),
+                right: apply_deadzone(magnitudes.right, deadzone),
+                up: apply_deadzone(magnitudes.up, deadzone),
+                down: apply_deadzone(magnitudes.down, deadzone),
+            }
+        } else {
+            PanMagnitudes::default()
+        };
+
+        let key_accel = |magnitude: f32| pan.keyboard_accel * pan.keyboard_response.apply(magnitude) * delta;
+        key_x.pos += key_accel(pan_magnitudes.right);
+        key_x.neg += key_accel(pan_magnitudes.left);
+        key_y.pos += key_accel(pan_magnitudes.up);
+        key_y.neg += key_accel(pan_magnitudes.down);
+
+        // A flick gives the pan a burst of speed as soon as pan input in a direction starts, so that
+        // taps coast
+        let last_magnitudes = std::mem::replace(&mut self.last_pan_magnitudes, pan_magnitudes);
+        if pan.flick_impulse != 0.0 {
+            let started = |magnitude: f32, last: f32| magnitude > 0.0 && last <= 0.0;
+            let flick = |magnitude, last| if started(magnitude, last) { pan.flick_impulse } else { 0.0 };
+            key_x.pos += flick(pan_magnitudes.right, last_magnitudes.right);
+            key_x.neg += flick(pan_magnitudes.left, last_magnitudes.left);
+            key_y.pos += flick(pan_magnitudes.up, last_magnitudes.up);
+            key_y.neg += flick(pan_magnitudes.down, last_magnitudes.down);
+        }
+
+        key_x = pan.opposing_keys.resolve(key_x);
+        key_y = pan.opposing_keys.resolve(key_y);
+
+        pan.input_combine.combine(edge_x, key_x).apply(&mut intent.pan_accel.x, &mut intent.pan_x_decel);
+        pan.input_combine.combine(edge_y, key_y).apply(&mut intent.pan_accel.y, &mut intent.pan_y_decel);
+
+        if controls.contains(CameraControls::TURN) && turn_chord {
+            if turn.right_keys.iter().any(|c| keyboard.pressed(*c)) {
+                turn_input.neg += turn.keyboard_accel * delta;
+            }
+
+            if turn.left_keys.iter().any(|c| keyboard.pressed(*c)) {
+                turn_input.pos += turn.keyboard_accel * delta;
+            }
+        }
+
+        // Relative mouse motion turns the camera directly, for free-look while the cursor is grabbed
+        if self.mouse_input == MouseInput::Relative && mouse_turn {
+            intent.turn_step -= self.mouse_motion.x * turn.motion_scale;
+        }
+
+        // Discrete steps fire once per key press rather than continuously while held
+        if controls.contains(CameraControls::TURN) && turn_chord {
+            let step_left = turn.step_left_keys.iter().any(|c| keyboard.just_pressed(*c));
+            let step_right = turn.step_right_keys.iter().any(|c| keyboard.just_pressed(*c));
+            intent.turn_step += (step_left as i32 - step_right as i32) as f32 * turn.step_angle;
+        }
+
+        // Smooth the combined turn input, so that brief entries into the mouse turn margin do not
+        // cause sharp changes in velocity
+        if turn.input_smoothing > 0.0 && delta > 0.0 {
+            let factor = 1.0 - (-delta / turn.input_smoothing).exp();
+            self.turn_input_rate += (turn_input.value() / delta - self.turn_input_rate) * factor;
+
+            if self.turn_input_rate.abs() < TURN_INPUT_RATE_EPSILON {
+                self.turn_input_rate = 0.0;
+            }
+
+            let value = self.turn_input_rate * delta;
+            turn_input = AxisInput { pos: value.max(0.0), neg: (-value).max(0.0) };
+        }
+
+        // The turn sources are combined before deceleration is decided, so that e.g a turn key held
+        // against the mouse turn margin decelerates according to the net turn rather than not at all
+        turn_input.apply_net(&mut intent.turn_accel, &mut intent.turn_decel);
+
+        // The region in which the mouse turns the camera is where the turn margin meets the pan margin
+        let scroll_in_turn_margin = zoom.suppress_scroll_in_turn_margin
+            && matches!(cursor, Some(cursor) if {
+                let at_side = cursor.x < pan.mouse_accel_margin || cursor.x > viewport.x - pan.mouse_accel_margin;
+                at_side && turn.in_mouse_turn_margin(cursor, viewport)
+            });
+        let scroll_pan = controls.contains(CameraControls::PAN)
+            && matches!(pan.scroll_pan_modifier, Some(key) if keyboard.pressed(key));
+        let scroll_zoom = controls.contains(CameraControls::ZOOM | CameraControls::SCROLL_ZOOM)
+            && zoom_chord
+            && !scroll_in_turn_margin
+            && !scroll_pan
+            && !matches!(zoom.suppress_scroll_key, Some(key) if keyboard.pressed(key));
+
+        let scroll = scroll
+            .map(|scroll| Vec2::new(apply_deadzone(scroll.x, deadzone), apply_deadzone(scroll.y, deadzone)))
+            .filter(|scroll| *scroll != Vec2::zero());
+
+        // With the scroll pan modifier held, the wheel pans horizontally. Scrolling down pans right,
+        // as do horizontal scroll wheels when scrolled right.
+        if let Some(scroll) = scroll.filter(|_| scroll_pan) {
+            let amount = if scroll.x != 0.0 { scroll.x } else { -scroll.y };
+
+            if amount > 0.0 {
+                intent.pan_x_decel.pos = false;
+            } else {
+                intent.pan_x_decel.neg = false;
+            }
+
+            intent.pan_accel.x += amount * pan.scroll_accel;
+            self.scroll_pan_interval_secs = now - self.last_scroll_pan_sec;
+            self.last_scroll_pan_sec = now;
+        }
+
+        if let Some(y) = scroll.map(|scroll| scroll.y).filter(|y| *y != 0.0 && scroll_zoom) {
+            if y > 0.0 {
+                intent.zoom_decel.pos = false;
+            } else {
+                intent.zoom_decel.neg = false;
+            }
+
+            let impulse = -y * zoom.scroll_accel;
+
+            if zoom.scroll_smoothing > 0.0 {
+                self.pending_scroll += impulse;
+                self.pending_scroll_rate = self.pending_scroll.abs() / zoom.scroll_smoothing;
+            } else {
+                intent.zoom_accel += impulse;
+            }
+
+            self.scroll_interval_secs = now - self.last_scroll_sec;
+            self.last_scroll_sec = now;
+        }
+
+        // Spread smoothed scroll impulses out over time, without decelerating while they ramp up
+        if self.pending_scroll != 0.0 {
+            let max_step = self.pending_scroll_rate * delta;
+            let step = clamp(self.pending_scroll, &(-max_step..=max_step));
+            intent.zoom_accel += step;
+            intent.zoom_decel = Deceleration { pos: false, neg: false };
+            self.pending_scroll -= step;
+        }
+
+        let zoom_axis = if controls.contains(CameraControls::ZOOM) {
+            let axis = self.zoom_input.unwrap_or_else(|| {
+                let zoom_in = zoom_chord && zoom.zoom_in_keys.iter().any(|c| keyboard.pressed(*c));
+                let zoom_out = zoom_chord && zoom.zoom_out_keys.iter().any(|c| keyboard.pressed(*c));
+                (zoom_out as i32 - zoom_in as i32) as f32
+            });
+
+            apply_deadzone(axis, deadzone)
+        } else {
+            0.0
+        };
+
+        intent.zoom_accel += zoom.keyboard_accel * clamp(zoom_axis, &(-1.0..=1.0)) * delta;
+
+        if zoom_axis < 0.0 {
+            intent.zoom_decel.pos = false;
+        } else if zoom_axis > 0.0 {
+            intent.zoom_decel.neg = false;
+        }
+
+        if controls.contains(CameraControls::ZOOM) && zoom_chord {
+            let step_in = zoom.step_in_keys.iter().any(|c| keyboard.just_pressed(*c));
+            let step_out = zoom.step_out_keys.iter().any(|c| keyboard.just_pressed(*c));
+            intent.zoom_step += (step_out as i32 - step_in as i32) as f32 * zoom.step_distance;
+        }
+
+        let pitch_enabled = controls.contains(CameraControls::PITCH) && pitch_chord;
+        let pitch_up = pitch_enabled && pitch.up_keys.iter().any(|c| keyboard.pressed(*c));
+        let pitch_down = pitch_enabled && pitch.down_keys.iter().any(|c| keyboard.pressed(*c));
+
+        if pitch_up || pitch_down {
+            intent.pitch = Some((pitch_up as i32 - pitch_down as i32) as f32);
+        }
+
+        intent
+    }
+
+    /// Derive the rotation and distance of the camera directly from its state, for cameras under
+    /// external control. The velocities are cleared so that nothing carries over when the player
+    /// regains control.
+    fn apply_external_state(&mut self, zoom: &ZoomSettings) {
+        let zoom_pitch = zoom.pitch_at(self.zoom_distance);
+        self.zoom_pitch = Some(zoom_pitch);
+        self.rotation = Quat::from_rotation_ypr(self.yaw, -(zoom_pitch + self.pitch_offset), 0.0);
+        self.camera_distance = f32::max(zoom.camera_distance_at(self.zoom_distance), MIN_CAMERA_DISTANCE);
+
+        self.zoom_velocity = 0.0;
+        self.pan_velocity = Vec2::zero();
+        self.turn_velocity = 0.0;
+        self.tween = None;
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn tick(
+        &mut self,
+        scroll: Option<Vec2>,
+        cursor: Option<Vec2>,
+        viewport: Vec2,
+        keyboard: &Input<KeyCode>,
+        settings: &CameraSettings,
+        cinematic: Option<&IdleCinematic>,
+        delta: f32,
+        now: f64,
+    ) {
+        let delta = delta * self.time_scale;
+        let mut intent = self.read_intent(scroll, cursor, viewport, keyboard, settings, delta, now);
+        let mut state = self.motion_state();
+
+        // Track how long there has been no input, and start the idle cinematic if it is due
+        if intent.is_active() {
+            self.idle_secs = 0.0;
+        } else {
+            self.idle_secs += delta;
+        }
+
+        if let Some(cinematic) = cinematic.filter(|c| self.idle_secs >= c.delay) {
+            state.pan_velocity.x = cinematic.pan_speed;
+            state.turn_velocity = cinematic.turn_speed;
+            intent.pan_x_decel = Deceleration { pos: false, neg: false };
+            intent.turn_decel = Deceleration { pos: false, neg: false };
+        }
+
+        // Carry momentum over from zooming to panning and vice versa when input for one stops
+        let zooming =
+            intent.zoom_accel != 0.0 || intent.zoom_step != 0.0 || intent.zoom_decel != Deceleration::default();
+        let panning = intent.pan_accel != Vec2::zero();
+        let (was_zooming, was_panning) = self.input_active;
+
+        if was_zooming && !zooming {
+            state.pan_velocity.y -= state.zoom_velocity * self.cross_coupling.zoom_to_pan;
+        }
+
+        if was_panning && !panning {
+            state.zoom_velocity += state.pan_velocity.length() * self.cross_coupling.pan_to_zoom;
+        }
+
+        self.input_active = (zooming, panning);
+
+        // Make any smooth move, unless input interrupts it
+        if intent.is_active() {
+            self.tween = None;
+        }
+
+        // Ease back into the distance range if it has changed under the camera, e.g when a settings
+        // override with a wider range is popped. Otherwise, the zoom distance is clamped to it.
+        let range = &settings.zoom.distance_range;
+        let range_changed = matches!(&self.last_distance_range, Some(last) if last != range);
+        if range_changed && !range.contains(&state.zoom_distance) {
+            state.zoom_range_return = true;
+        }
+
+        self.last_distance_range = Some(range.clone());
+
+        // A move at a lower priority than that at which the camera has since been acquired stops
+        if matches!(self.tween, Some(tween) if !self.accepts(tween.priority)) {
+            self.tween = None;
+        }
+
+        if let Some(tween) = &mut self.tween {
+            let pose = tween.advance(delta);
+            state.looking_at = pose.looking_at;
+            state.yaw = pose.yaw;
+            state.zoom_distance = pose.zoom_distance;
+            state.zoom_range_return = false;
+            state.pan_velocity = Vec2::zero();
+            state.zoom_velocity = 0.0;
+            state.turn_velocity = 0.0;
+
+            if tween.is_finished() {
+                self.tween = None;
+            }
+        }
+
+        let motion = settings.motion();
+        state.integrate(&intent, &motion, delta);
+
+        // Settle a released free turn onto the nearest snap increment
+        let turning =
+            intent.turn_accel != 0.0 || intent.turn_step != 0.0 || intent.turn_decel != Deceleration::default();
+        self.turn_released = (self.turn_released || turning) && self.tween.is_none();
+
+        let snap = settings.turn.snap_on_release.filter(|increment| *increment > 0.0);
+        if let Some(increment) = snap.filter(|_| self.turn_released && !turning) {
+            if state.turn_velocity.abs() < TURN_SNAP_SPEED {
+                // The nearest increment may not be a yaw which the camera can turn to, in which case
+                // it settles at the nearest yaw which it can
+                let nearest = constrain_yaw((state.yaw / increment).round() * increment, &motion.turn);
+                let remaining = allowed_yaw_delta(state.yaw, nearest, &motion.turn);
+                state.turn_velocity = 0.0;
+
+                if remaining.abs() < TURN_SNAP_EPSILON {
+                    state.rotate(remaining);
+                    self.turn_released = false;
+                } else {
+                    state.rotate(remaining * (1.0 - (-delta / TURN_SNAP_EASE_SECS).exp()));
+                }
+            }
+        }
+
+        self.set_motion_state(state);
+    }
+}
+
+/// The runtime state of an [`RtsCamera`], including its velocities, as captured by
+/// [`RtsCamera::snapshot`]. With the `serde` feature, this can be serialized, e.g for rollback
+/// netcode. Scroll times which are not finite, such as the interval before there have been two
+/// scroll events, are `None`, as these cannot be represented in formats such as JSON.
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RtsCameraSnapshot {
+    pub looking_at: Vec3,
+    pub rotation: Quat,
+    pub yaw: f32,
+    pub pitch_offset: f32,
+    pub zoom_pitch: Option<f32>,
+    pub zoom_distance: f32,
+    pub pan_velocity: Vec2,
+    pub zoom_velocity: f32,
+    pub turn_velocity: f32,
+    pub last_scroll_sec: Option<f64>,
+    pub scroll_interval_secs: Option<f64>,
+    pub last_scroll_pan_sec: Option<f64>,
+    pub scroll_pan_interval_secs: Option<f64>,
+    pub pending_scroll: f32,
+    pub pending_scroll_rate: f32,
+    pub turn_input_rate: f32,
+}
+
+/// Whether all of the given keys are pressed, which is the case if there are none.
+fn all_pressed(keyboard: &Input<KeyCode>, keys: &[KeyCode]) -> bool {
+    keys.iter().all(|c| keyboard.pressed(*c))
+}
+
+/// Treat input with a magnitude below the deadzone as zero.
+fn apply_deadzone(input: f32, deadzone: f32) -> f32 {
+    if input.abs() < deadzone {
+        0.0
+    } else {
+        input
+    }
+}
+
+/// The mean of the given points, or `None` if there are none.
+fn centroid(points: &[Vec3]) -> Option<Vec3> {
+    if points.is_empty() {
+        return None;
+    }
+
+    Some(points.iter().fold(Vec3::zero(), |sum, p| sum + *p) / points.len() as f32)
+}
+
+/// Transform a world space point into the clip space of the given camera with the given transform.
+fn world_to_clip(transform: &Transform, camera: &Camera, world: Vec3) -> Vec4 {
+    camera.projection_matrix * transform.compute_matrix().inverse() * world.extend(1.0)
+}
+
+/// The position in the window of the given point in clip space, or `None` if the point is behind the
+/// camera or the window has no area.
+fn clip_to_screen(clip: Vec4, window: &Window) -> Option<Vec2> {
+    let size = Vec2::new(window.width(), window.height());
+
+    if size.x <= 0.0 || size.y <= 0.0 || clip.w <= 0.0 {
+        return None;
+    }
+
+    let ndc = Vec2::new(clip.x, clip.y) / clip.w;
+    Some((ndc + Vec2::one()) / 2.0 * size)
+}
+
+/// The distance from which a sphere of the given radius fits within the view of a perspective
+/// camera along its narrower axis.
+fn framing_distance(radius: f32, camera: &Camera) -> f32 {
+    let half_fov = half_fovs(camera);
+    radius / f32::min(half_fov.x, half_fov.y).sin()
+}
+
+/// The horizontal and vertical half angles of the field of view of a perspective camera, in
+/// radians, derived from its projection matrix.
+fn half_fovs(camera: &Camera) -> Vec2 {
+    let projection = camera.projection_matrix;
+    Vec2::new(projection.x_axis.x.recip().atan(), projection.y_axis.y.recip().atan())
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ZoomSettings {
+    /// The minimum and maximum angle in radians from the target
+    pub angle_range: RangeInclusive<f32>,
+
+    /// At the minimum distance, the angle to the target will be equal to `min_angle`, and vice versa.
+    /// In other words, at these points the camera will only zoom in or out rather than also changing
+    /// its angle - the angle only changes within this distance zone.
+    pub angle_change_zone: RangeInclusive<f32>,
+
+    /// Whether to invert the relationship between zoom distance and angle, so that the camera is
+    /// at the maximum angle (more top-down) when zoomed in, and at the minimum angle (more oblique)
+    /// when zoomed out.
+    pub invert_angle: bool,
+
+    /// The time constant in seconds with which the pitch follows the pitch of the zoom distance,
+    /// so that sudden changes in zoom distance such as zoom steps tilt the camera smoothly. If this
+    /// is zero, the pitch follows the zoom distance exactly.
+    pub pitch_smoothing: f32,
+
+    /// The maximum rate in radians per second at which the pitch follows the pitch of the zoom
+    /// distance, so that zooming quickly across `angle_change_zone` does not tilt the camera too
+    /// abruptly. If this is `None`, the pitch change is not limited.
+    pub max_pitch_rate: Option<f32>,
+
+    /// The zone of zoom distance within which the camera actually moves towards or away from the
+    /// target. Outside of this zone, the camera stays at the distance of the nearest end of the zone
+    /// and zooming only changes the angle (if within `angle_change_zone`). For instance, with an
+    /// angle change zone of `5.0..=30.0` and a distance change zone of `30.0..=100.0`, zooming in
+    /// close only tilts the camera, and zooming out far only moves it away. If this is `None`, the
+    /// distance changes across the whole distance range.
+    pub distance_change_zone: Option<RangeInclusive<f32>>,
+
+    /// The minimum and maximum distance from the target
+    pub distance_range: RangeInclusive<f32>,
+    /// The time constant in seconds with which the zoom distance eases back into `distance_range`
+    /// when the range changes so that it no longer contains the zoom distance, e.g after zooming out
+    /// past the usual limit during a settings override with a wider range. If this is zero, the
+    /// zoom distance snaps back into the range. Smooth moves and zoom distances set directly are
+    /// always clamped to the range.
+    pub range_return_smoothing: f32,
+
+    /// The current velocity at which the camera is zooming in or out
+    pub velocity: f32,
+    /// The maximum velocity at which the camera can zoom in or out
+    pub max_velocity: f32,
+    /// The acceleration which the scroll wheel applies to the camera zoom while scrolling. Note
+    /// that because of the discrete way in which scroll events are sent to the application,
+    /// the delta time is *not* multiplied to the scroll accel value before it is added to the
+    /// velocity. Therefore, this acts as the change in velocity per line or pixel scrolled, rather
+    /// than the acceleration applied over a second of input.
+    pub scroll_accel: f32,
+    /// How the scroll events received since the last update are combined into the scroll input
+    pub scroll_accumulation: ScrollAccumulation,
+    /// The minimum and maximum grace period in seconds after a scroll event during which the zoom
+    /// is not decelerated. During a burst of scrolling, the grace period is extended to cover the
+    /// interval between scroll events, up to the maximum, so that zooming does not stutter on
+    /// scroll wheels with coarse steps. When scrolling slows down so that the interval is longer
+    /// than the maximum, the grace period shrinks back to the minimum.
+    pub scroll_grace_secs: RangeInclusive<f64>,
+    /// The time in seconds over which each scroll impulse is spread out, so that the zoom velocity
+    /// ramps up rather than jumping at the start of a fast scroll. While an impulse is being
+    /// applied, the zoom is not decelerated. If this is zero, scroll impulses are applied at once.
+    pub scroll_smoothing: f32,
+    /// Whether to ignore the scroll wheel while the cursor is in the region in which the mouse
+    /// turns the camera, to avoid accidental zooms while turning
+    pub suppress_scroll_in_turn_margin: bool,
+    /// A key which, while held, makes the camera ignore the scroll wheel for zooming, e.g so that a
+    /// focused scrollable panel can scroll instead. If this is `None`, scrolling always zooms.
+    pub suppress_scroll_key: Option<KeyCode>,
+    /// The acceleration which the keyboard applies to the camera zoom while scrolling
+    pub keyboard_accel: f32,
+    /// The deceleration of the camera zoom while nothing is causing it to zoom in or out
+    pub idle_deceleration: f32,
+    /// The deceleration of the camera zoom while coasting inwards. If this is `None`, the idle
+    /// deceleration is used.
+    pub idle_deceleration_in: Option<f32>,
+    /// The deceleration of the camera zoom while coasting outwards. If this is `None`, the idle
+    /// deceleration is used.
+    pub idle_deceleration_out: Option<f32>,
+    /// How the zoom deceleration is applied over time
+    pub decel_model: DecelModel,
+    /// A multiplier applied to the zoom acceleration from all input sources. This is intended to
+    /// be exposed to players as a sensitivity setting.
+    pub sensitivity: f32,
+
+    /// Whether to extend the far clip plane of the camera's `PerspectiveProjection` when it is
+    /// added if it is too close to see the ground at the maximum zoom distance. If this is `false`,
+    /// a warning is logged instead.
+    pub auto_extend_far_plane: bool,
+
+    /// Modifier keys which must all be held for the scroll wheel and zoom keys to zoom the camera,
+    /// e.g to zoom with Ctrl+Scroll. If this is empty, no modifiers are required.
+    pub modifier_keys: Cow<'static, [KeyCode]>,
+    /// Keys which will cause the camera to zoom in
+    pub zoom_in_keys: Cow<'static, [KeyCode]>,
+    /// Keys which will cause the camera to zoom out
+    pub zoom_out_keys: Cow<'static, [KeyCode]>,
+
+    /// The distance by which the camera zooms in or out at once when a zoom step key is pressed
+    pub step_distance: f32,
+    /// Keys which will cause the camera to zoom in by one step per press, rather than continuously
+    /// while held
+    pub step_in_keys: Cow<'static, [KeyCode]>,
+    /// Keys which will cause the camera to zoom out by one step per press, rather than
+    /// continuously while held
+    pub step_out_keys: Cow<'static, [KeyCode]>,
+}
+
+impl ZoomSettings {
+    pub const fn new() -> Self {
+        ZoomSettings {
+            angle_range: 0.5705693..=1.1637539,
+            angle_change_zone: 5.0..=100.0,
+            invert_angle: false,
+            pitch_smoothing: 0.0,
+            max_pitch_rate: None,
+            distance_change_zone: None,
+            distance_range: 5.0..=100.0,
+            range_return_smoothing: 0.25,
+            velocity: 0.0,
+            max_velocity: 5.0,
+            scroll_accel: 5.0,
+            scroll_accumulation: ScrollAccumulation::Latest,
+            scroll_grace_secs: 0.05..=0.2,
+            scroll_smoothing: 0.0,
+            suppress_scroll_in_turn_margin: false,
+            suppress_scroll_key: None,
+            keyboard_accel: 5.0,
+            idle_deceleration: 5.0,
+            idle_deceleration_in: None,
+            idle_deceleration_out: None,
+            decel_model: DecelModel::Linear,
+            sensitivity: 1.0,
+            auto_extend_far_plane: false,
+            modifier_keys: Cow::Borrowed(&[]),
+            zoom_in_keys: Cow::Borrowed(&[KeyCode::Equals, KeyCode::NumpadAdd]),
+            zoom_out_keys: Cow::Borrowed(&[KeyCode::NumpadSubtract, KeyCode::Minus]),
+            step_distance: 10.0,
+            step_in_keys: Cow::Borrowed(&[]),
+            step_out_keys: Cow::Borrowed(&[]),
+        }
+    }
+
+    /// The scroll grace period in seconds given the interval between the last two scroll events.
+    pub fn scroll_grace(&self, scroll_interval: f64) -> f64 {
+        let (min, max) = (*self.scroll_grace_secs.start(), *self.scroll_grace_secs.end());
+
+        if scroll_interval <= max {
+            (scroll_interval * SCROLL_GRACE_INTERVAL_FACTOR).max(min).min(max)
+        } else {
+            min
+        }
+    }
+
+    /// The pitch of the camera in radians at the given zoom distance, before any manual pitch
+    /// adjustment.
+    pub fn pitch_at(&self, distance: f32) -> f32 {
+        self.motion().pitch_at(distance)
+    }
+
+    /// The part of these settings which the motion model uses.
+    pub fn motion(&self) -> ZoomMotion {
+        ZoomMotion {
+            angle_range: self.angle_range.clone(),
+            angle_change_zone: self.angle_change_zone.clone(),
+            invert_angle: self.invert_angle,
+            distance_change_zone: self.distance_change_zone.clone(),
+            distance_range: self.distance_range.clone(),
+            range_return_smoothing: self.range_return_smoothing,
+            max_velocity: self.max_velocity,
+            idle_deceleration: self.idle_deceleration,
+            idle_deceleration_in: self.idle_deceleration_in,
+            idle_deceleration_out: self.idle_deceleration_out,
+            decel_model: self.decel_model,
+            sensitivity: self.sensitivity,
+            pitch_smoothing: self.pitch_smoothing,
+            max_pitch_rate: self.max_pitch_rate,
+        }
+    }
+
+    /// The distance of the camera from the target at the given zoom distance.
+    pub fn camera_distance_at(&self, zoom_distance: f32) -> f32 {
+        self.motion().camera_distance_at(zoom_distance)
+    }
+
+    /// The minimum far clip plane distance which allows the ground to be seen at the maximum zoom
+    /// distance, given the vertical field of view in radians. This is the distance to where the top
+    /// edge of the view meets the ground, or to the target if the top edge is above the horizon.
+    pub fn required_far_plane(&self, fov: f32) -> f32 {
+        let zoom_distance = *self.distance_range.end();
+        let distance = self.camera_distance_at(zoom_distance);
+        let pitch = self.pitch_at(zoom_distance);
+        let top_edge_angle = pitch - fov / 2.0;
+
+        if top_edge_angle > 0.0 {
+            let height = distance * pitch.sin();
+            f32::max(distance, height / top_edge_angle.sin())
+        } else {
+            distance
+        }
+    }
+}
+
+impl Default for ZoomSettings {
+    fn default() -> Self { ZoomSettings::new() }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PanSettings {
+    /// The acceleration which the mouse applies to the camera's panning motion.
+    pub mouse_accel: f32,
+    /// The minimum distance from the edge of the window the mouse must be in order for the camera
+    /// to begin panning.
+    pub mouse_accel_margin: f32,
+    /// How the acceleration from the mouse depends on how far into the margin the cursor is, where
+    /// 0 is the inside of the margin and 1 is the edge of the window. By default, the cursor gives
+    /// the full acceleration anywhere in the margin.
+    pub edge_response: ResponseCurve,
+    /// Whether the mouse only pans the camera while the cursor is moving towards the edge that it is
+    /// at, for a "push to pan" feel. As soon as the cursor stops, the camera decelerates as usual,
+    /// even if the cursor is still within the margin.
+    pub edge_pan_requires_outward_motion: bool,
+    /// The acceleration that they keyboard applies to the camera's panning motion
+    pub keyboard_accel: f32,
+    /// How the acceleration from the keyboard depends on the magnitude of the pan input. This only
+    /// makes a difference for analog `pan_input`, as keys always have a magnitude of 1.
+    pub keyboard_response: ResponseCurve,
+    /// The change in pan velocity applied at once when pan input in a direction starts, e.g when a
+    /// pan key is pressed, on top of the keyboard acceleration while it is held. Together with a
+    /// low `idle_deceleration`, this makes brief taps flick the camera so that it coasts. As with
+    /// `scroll_accel`, this is not multiplied by the delta time. If this is zero, there is no flick.
+    pub flick_impulse: f32,
+    /// The maximum velocity at which the camera may pan
+    pub max_speed: f32,
+    /// The deceleration of the panning while nothing is accelerating it in a certain direction
+    pub idle_deceleration: f32,
+    /// The deceleration of the panning while input is accelerating it against its current
+    /// direction of motion, e.g when reversing. If this is `None`, `idle_deceleration` is used
+    /// instead. Setting this higher than `idle_deceleration` makes reversing feel snappy while
+    /// still allowing the camera to glide smoothly when input is released.
+    pub reverse_deceleration: Option<f32>,
+    /// How the pan deceleration is applied over time
+    pub decel_model: DecelModel,
+    /// How pan input from the mouse at the screen edges and from the keyboard is combined when
+    /// both are active on the same axis
+    pub input_combine: InputCombine,
+    /// What happens when pan keys in opposite directions on the same axis are held at once
+    pub opposing_keys: OpposingKeys,
+    /// A key which, while held, makes the scroll wheel pan the camera horizontally instead of
+    /// zooming it, as in many map tools. If this is `None`, the scroll wheel always zooms.
+    pub scroll_pan_modifier: Option<KeyCode>,
+    /// The change in pan velocity per line or pixel scrolled while the scroll pan modifier is held.
+    /// As with the zoom settings' `scroll_accel`, this is not multiplied by the delta time, and the
+    /// pan is not decelerated during the zoom settings' scroll grace period after each event.
+    pub scroll_accel: f32,
+    /// A per-axis multiplier applied to the pan acceleration from all input sources. This is
+    /// intended to be exposed to players as a sensitivity setting.
+    pub sensitivity: Vec2,
+
+    /// The effect of zoom distance on pan speed. This can be set to make panning faster when more
+    /// zoomed out. The start value of this range is the factor at the minimum of the zoom settings'
+    /// `distance_range`, and the end is the factor at the maximum. The factor will be interpolated
+    /// according to the zoom distance along `pan_speed_zoom_factor_easing`.
+    pub pan_speed_zoom_factor_range: RangeInclusive<f32>,
+    /// The curve along which the pan speed zoom factor changes with the zoom distance. An easing
+    /// curve other than linear avoids an abrupt-feeling change in pan speed at the ends of the
+    /// zoom range.
+    pub pan_speed_zoom_factor_easing: Easing,
+
+    /// Modifier keys which must all be held for the pan keys to pan the camera. This does not affect
+    /// panning with the mouse at the edges of the screen. If this is empty, no modifiers are
+    /// required.
+    pub modifier_keys: Cow<'static, [KeyCode]>,
+    /// The keys which will cause the camera to pan left
+    pub left_keys: Cow<'static, [KeyCode]>,
+    /// The keys which will cause the camera to pan right
+    pub right_keys: Cow<'static, [KeyCode]>,
+    /// The keys which will cause the camera to pan up
+    pub up_keys: Cow<'static, [KeyCode]>,
+    /// The keys which will cause the camera to pan down
+    pub down_keys: Cow<'static, [KeyCode]>,
+}
+
+impl PanSettings {
+    pub const fn new() -> Self {
+        PanSettings {
+            mouse_accel: 15.0,
+            mouse_accel_margin: 10.0,
+            edge_response: ResponseCurve::Constant,
+            edge_pan_requires_outward_motion: false,
+            keyboard_accel: 5.0,
+            keyboard_response: ResponseCurve::Linear,
+            flick_impulse: 0.0,
+            max_speed: 5.0,
+            idle_deceleration: 17.5,
+            reverse_deceleration: None,
+            decel_model: DecelModel::Linear,
+            input_combine: InputCombine::Sum,
+            opposing_keys: OpposingKeys::Cancel,
+            scroll_pan_modifier: None,
+            scroll_accel: 5.0,
+            sensitivity: Vec2::one(),
+            pan_speed_zoom_factor_range: 1.0..=2.0,
+            pan_speed_zoom_factor_easing: Easing::Linear,
+            modifier_keys: Cow::Borrowed(&[]),
+            left_keys: Cow::Borrowed(&[KeyCode::Left, KeyCode::A]),
+            right_keys: Cow::Borrowed(&[KeyCode::Right, KeyCode::D]),
+            up_keys: Cow::Borrowed(&[KeyCode::Up, KeyCode::W]),
+            down_keys: Cow::Borrowed(&[KeyCode::Down, KeyCode::S]),
+        }
+    }
+
+    /// The part of these settings which the motion model uses.
+    pub fn motion(&self) -> PanMotion {
+        PanMotion {
+            max_speed: self.max_speed,
+            idle_deceleration: self.idle_deceleration,
+            reverse_deceleration: self.reverse_deceleration,
+            decel_model: self.decel_model,
+            sensitivity: self.sensitivity,
+            pan_speed_zoom_factor_range: self.pan_speed_zoom_factor_range.clone(),
+            pan_speed_zoom_factor_easing: self.pan_speed_zoom_factor_easing,
+        }
+    }
+
+    /// The factor by which the pan speed is multiplied at the given zoom distance, interpolated
+    /// over the zoom settings' distance range. If the distance range has equal ends, this is the
+    /// start of `pan_speed_zoom_factor_range`.
+    pub fn speed_zoom_factor(&self, zoom_distance: f32, zoom: &ZoomSettings) -> f32 {
+        self.motion().speed_zoom_factor(zoom_distance, &zoom.motion())
+    }
+}
+
+impl Default for PanSettings {
+    fn default() -> Self { PanSettings::new() }
+}
+
+/// A source of pan input.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum InputSource {
+    /// The mouse at the edges of the screen
+    MouseEdge,
+    /// The keyboard, or whatever is overriding it through [`RtsCamera::pan_input`]
+    Keyboard,
+}
+
+/// How pan input from multiple sources on the same axis is combined.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum InputCombine {
+    /// The accelerations from both sources are added together
+    Sum,
+    /// Only the source with the larger acceleration on the axis is used
+    Max,
+    /// Only the given source is used if it has input on the axis, and otherwise the other source
+    Priority(InputSource),
+}
+
+impl InputCombine {
+    fn combine(self, edge: AxisInput, keyboard: AxisInput) -> AxisInput {
+        match self {
+            InputCombine::Sum => AxisInput {
+                pos: edge.pos + keyboard.pos,
+                neg: edge.neg + keyboard.neg,
+            },
+            InputCombine::Max if edge.value().abs() > keyboard.value().abs() => edge,
+            InputCombine::Max => keyboard,
+            InputCombine::Priority(InputSource::MouseEdge) if edge.is_active() => edge,
+            InputCombine::Priority(InputSource::Keyboard) if !keyboard.is_active() => edge,
+            InputCombine::Priority(_) => keyboard,
+        }
+    }
+}
+
+/// What happens to the zoom distance when focusing the camera on a point, e.g with
+/// [`RtsCamera::focus_smooth`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ZoomBehavior {
+    /// The zoom distance is kept as it is
+    Keep,
+    /// The camera zooms to the given zoom distance
+    SetTo(f32),
+    /// The camera zooms to its `default_zoom_distance`
+    Default,
+}
+
+/// How the scroll events received since the last update of the camera are combined.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ScrollAccumulation {
+    /// Only the latest scroll event is used, and any earlier ones are dropped
+    Latest,
+    /// The deltas of all of the scroll events are summed, so that no input is dropped when there
+    /// are several events per frame, e.g with high frequency scroll wheels or during frame hitches
+    Sum,
+}
+
+/// How the mouse drives the camera.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MouseInput {
+    /// The absolute cursor position drives edge panning and turning in the mouse turn margin
+    Cursor,
+    /// Relative mouse motion turns the camera, and the cursor position is ignored. This is for
+    /// when the cursor is grabbed or hidden, in which case its position does not change.
+    Relative,
+}
+
+/// How much of the camera's momentum carries over between zooming and panning when input for one
+/// stops, for a flowing feel in compound motions. Both fractions are zero by default, i.e there is
+/// no coupling.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct CrossCoupling {
+    /// The fraction of the zoom velocity which is added to the forward pan velocity when zoom input
+    /// stops, so that zooming in nudges the camera forward and zooming out nudges it back
+    pub zoom_to_pan: f32,
+    /// The fraction of the pan speed which is added to the zoom velocity when pan input stops, so
+    /// that the camera pulls out slightly after a pan
+    pub pan_to_zoom: f32,
+}
+
+/// What happens when pan input in opposite directions on the same axis is held at once, such as
+/// both the left and right pan keys.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum OpposingKeys {
+    /// The inputs cancel out to their net value, and the camera is decelerated according to the
+    /// direction of the net input. If they cancel out completely, the camera decelerates to a stop
+    /// as if there were no input.
+    Cancel,
+    /// The input on the axis is ignored entirely, as if neither direction were held
+    Ignore,
+}
+
+impl OpposingKeys {
+    fn resolve(self, input: AxisInput) -> AxisInput {
+        if input.pos <= 0.0 || input.neg <= 0.0 {
+            return input;
+        }
+
+        match self {
+            OpposingKeys::Cancel => {
+                let value = input.value();
+                AxisInput { pos: value.max(0.0), neg: (-value).max(0.0) }
+            }
+            OpposingKeys::Ignore => AxisInput::default(),
+        }
+    }
+}
+
+/// The acceleration from input on a single axis, in each direction.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+struct AxisInput {
+    pos: f32,
+    neg: f32,
+}
+
+impl AxisInput {
+    fn value(&self) -> f32 {
+        self.pos - self.neg
+    }
+
+    fn is_active(&self) -> bool {
+        self.pos > 0.0 || self.neg > 0.0
+    }
+
+    /// Add the acceleration to the axis, and stop deceleration against the directions of input.
+    fn apply(&self, accel: &mut f32, decel: &mut Deceleration) {
+        *accel += self.value();
+
+        if self.pos > 0.0 {
+            decel.pos = false;
+        }
+
+        if self.neg > 0.0 {
+            decel.neg = false;
+        }
+    }
+
+    /// Add the acceleration to the axis, and stop deceleration only against the direction of the
+    /// net input. Opposing inputs which cancel out are decelerated against as if there were none.
+    fn apply_net(&self, accel: &mut f32, decel: &mut Deceleration) {
+        let value = self.value();
+        *accel += value;
+
+        if value > 0.0 {
+            decel.pos = false;
+        } else if value < 0.0 {
+            decel.neg = false;
+        }
+    }
+}
+
+/// The magnitude of pan input in each direction, from 0 (not pressed) to 1 (fully pressed).
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct PanMagnitudes {
+    pub left: f32,
+    pub right: f32,
+    pub up: f32,
+    pub down: f32,
+}
+
+impl PanMagnitudes {
+    /// Read the magnitudes from the pan keys, where a pressed key has a magnitude of 1.
+    pub fn from_keys(keyboard: &Input<KeyCode>, pan: &PanSettings) -> Self {
+        let magnitude = |keys: &[KeyCode]| {
+            if keys.iter().any(|c| keyboard.pressed(*c)) {
+                1.0
+            } else {
+                0.0
+            }
+        };
+
+        PanMagnitudes {
+            left: magnitude(&pan.left_keys),
+            right: magnitude(&pan.right_keys),
+            up: magnitude(&pan.up_keys),
+            down: magnitude(&pan.down_keys),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TurnSettings {
+    /// The distance that the mouse must be from the edge of the screen given by `mouse_turn_edge`
+    /// before it will start turning, provided that it is within the pan settings margin. This is
+    /// measured as a ratio of the height dimension of the screen.
+    pub mouse_turn_margin: f32,
+    /// The largest ratio of the height of the screen which the mouse turn margin may cover, however
+    /// large `mouse_turn_margin` is. This keeps the cursor from turning the camera almost anywhere
+    /// on the screen with an aggressive margin, e.g in a short window.
+    pub max_mouse_turn_fraction: f32,
+    /// The edge of the screen from which `mouse_turn_margin` is measured
+    pub mouse_turn_edge: VerticalEdge,
+    /// How the mouse behaves in the corners at `mouse_turn_edge`, where it is within both the mouse
+    /// turn margin and the pan settings' margin for panning vertically
+    pub corner_policy: CornerPolicy,
+    /// The range of yaw that the camera may turn, in radians. This is ignored if `yaw_sectors` is
+    /// set.
+    pub yaw_range: RangeInclusive<f32>,
+    /// A list of disjoint sectors of yaw, in radians between 0 and TAU, which the camera may turn
+    /// within. When turning would take the camera out of all of the sectors, its yaw is snapped to
+    /// the nearest sector boundary and it stops turning. If this is `None`, `yaw_range` is used.
+    pub yaw_sectors: Option<Vec<RangeInclusive<f32>>>,
+    /// The acceleration which the mouse applies to the camera's turning velocity (measured in
+    /// radians per seconds squared)
+    pub mouse_accel: f32,
+    /// The acceleration which the keyboard applies to the camera's turning velocity (measured in
+    /// radians per seconds squared)
+    pub keyboard_accel: f32,
+    pub max_speed: f32,
+    pub idle_deceleration: f32,
+    /// How the turn deceleration is applied over time
+    pub decel_model: DecelModel,
+    /// A multiplier applied to the turn acceleration from all input sources. This is intended to
+    /// be exposed to players as a sensitivity setting.
+    pub sensitivity: f32,
+    /// The angle in radians by which the camera turns per logical pixel of horizontal mouse motion,
+    /// when the camera's mouse input is [`MouseInput::Relative`]
+    pub motion_scale: f32,
+    /// The time constant in seconds of the low-pass filter applied to the combined turn input, so
+    /// that edge turning feels more deliberate and does not twitch as the cursor moves in and out
+    /// of the turn margin. If this is zero, the turn input is applied as is.
+    pub input_smoothing: f32,
+    /// The increment in radians, e.g `FRAC_PI_4` for compass headings, onto the nearest multiple of
+    /// which the yaw eases once turning input is released and the camera has slowed down. Turning
+    /// is still free while there is input. If the nearest multiple is outside the yaw range or
+    /// sectors, the yaw eases to the nearest yaw within them instead. If this is `None`, the yaw
+    /// stays wherever turning stops.
+    pub snap_on_release: Option<f32>,
+    /// Modifier keys which must all be held for the turn keys and the mouse turn margin to turn the
+    /// camera. If this is empty, no modifiers are required.
+    pub modifier_keys: Cow<'static, [KeyCode]>,
+    /// The keys which will cause the camera to turn left
+    pub left_keys: Cow<'static, [KeyCode]>,
+    /// The keys which will cause the camera to turn right
+    pub right_keys: Cow<'static, [KeyCode]>,
+    /// The angle in radians by which the camera turns at once when a turn step key is pressed
+    pub step_angle: f32,
+    /// The keys which will cause the camera to turn left by one step per press, rather than
+    /// continuously while held
+    pub step_left_keys: Cow<'static, [KeyCode]>,
+    /// The keys which will cause the camera to turn right by one step per press, rather than
+    /// continuously while held
+    pub step_right_keys: Cow<'static, [KeyCode]>,
+}
+
+impl TurnSettings {
+    pub const fn new() -> Self {
+        TurnSettings {
+            mouse_turn_margin: 0.25,
+            max_mouse_turn_fraction: 0.5,
+            mouse_turn_edge: VerticalEdge::Top,
+            corner_policy: CornerPolicy::Both,
+            yaw_range: 0.0..=TAU,
+            yaw_sectors: None,
+            mouse_accel: 0.3,
+            keyboard_accel: 1.8,
+            max_speed: 1.5,
+            idle_deceleration: 5.0,
+            decel_model: DecelModel::Linear,
+            sensitivity: 1.0,
+            motion_scale: 0.005,
+            input_smoothing: 0.0,
+            snap_on_release: None,
+            modifier_keys: Cow::Borrowed(&[]),
+            left_keys: Cow::Borrowed(&[KeyCode::Q]),
+            right_keys: Cow::Borrowed(&[KeyCode::E]),
+            step_angle: FRAC_PI_2,
+            step_left_keys: Cow::Borrowed(&[]),
+            step_right_keys: Cow::Borrowed(&[]),
+        }
+    }
+
+    /// The part of these settings which the motion model uses.
+    pub fn motion(&self) -> TurnMotion<'_> {
+        TurnMotion {
+            yaw_range: self.yaw_range.clone(),
+            yaw_sectors: self.yaw_sectors.as_deref(),
+            max_speed: self.max_speed,
+            idle_deceleration: self.idle_deceleration,
+            decel_model: self.decel_model,
+            sensitivity: self.sensitivity,
+        }
+    }
+}
+
+impl TurnSettings {
+    /// Whether the given cursor position is within the vertical region in which the mouse turns the
+    /// camera, which covers at most `max_mouse_turn_fraction` of the viewport height. Like Bevy's
+    /// cursor position, the cursor is measured in logical pixels with the origin at the bottom left
+    /// of the viewport, so the top edge is at `viewport.y`.
+    pub fn in_mouse_turn_margin(&self, cursor: Vec2, viewport: Vec2) -> bool {
+        let margin = viewport.y * self.mouse_turn_margin.min(self.max_mouse_turn_fraction);
+
+        match self.mouse_turn_edge {
+            VerticalEdge::Top => cursor.y > viewport.y - margin,
+            VerticalEdge::Bottom => cursor.y < margin,
+        }
+    }
+}
+
+impl Default for TurnSettings {
+    fn default() -> Self { TurnSettings::new() }
+}
+
+/// How the mouse behaves in the corners of the screen at the turn edge, where it is in both the
+/// region in which it turns the camera and the margin in which it pans it vertically.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CornerPolicy {
+    /// The mouse both turns the camera and pans it vertically
+    Both,
+    /// The mouse only turns the camera
+    TurnPriority,
+    /// The mouse only pans the camera, both horizontally and vertically
+    PanPriority,
+    /// The mouse turns the camera and pans it vertically in proportion to how far into the side
+    /// and turn edge margins it is respectively, so that the response changes smoothly with the
+    /// angle of the cursor into the corner
+    Blend,
+}
+
+impl CornerPolicy {
+    /// The weights of the turn and vertical pan acceleration in a corner, given how far the cursor
+    /// is into the side and turn edge margins.
+    fn weights(self, side_depth: f32, edge_depth: f32) -> (f32, f32) {
+        match self {
+            CornerPolicy::Both => (1.0, 1.0),
+            CornerPolicy::TurnPriority => (1.0, 0.0),
+            CornerPolicy::PanPriority => (0.0, 1.0),
+            CornerPolicy::Blend => {
+                let total = side_depth + edge_depth;
+                (side_depth / total, edge_depth / total)
+            }
+        }
+    }
+}
+
+/// The top or bottom edge of the screen.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum VerticalEdge {
+    Top,
+    Bottom,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PitchSettings {
+    /// The range of manual adjustment which may be applied to the pitch derived from the zoom
+    /// distance, in radians
+    pub offset_range: RangeInclusive<f32>,
+    /// The speed at which the keyboard adjusts the pitch, in radians per second
+    pub speed: f32,
+    /// The speed in radians per second at which the manual pitch adjustment returns to zero while
+    /// there is no pitch input. If this is `None`, the pitch stays where it was left.
+    pub auto_recenter: Option<f32>,
+    /// Modifier keys which must all be held for the pitch keys to pitch the camera. If this is
+    /// empty, no modifiers are required.
+    pub modifier_keys: Cow<'static, [KeyCode]>,
+    /// The keys which will cause the camera to pitch up (towards looking straight down). There are
+    /// none by default, so manual pitching is opt-in.
+    pub up_keys: Cow<'static, [KeyCode]>,
+    /// The keys which will cause the camera to pitch down (towards the horizon). There are none by
+    /// default.
+    pub down_keys: Cow<'static, [KeyCode]>,
+}
+
+impl PitchSettings {
+    pub const fn new() -> Self {
+        PitchSettings {
+            offset_range: -0.35..=0.35,
+            speed: 0.5,
+            auto_recenter: None,
+            modifier_keys: Cow::Borrowed(&[]),
+            up_keys: Cow::Borrowed(&[]),
+            down_keys: Cow::Borrowed(&[]),
+        }
+    }
+
+    /// The part of these settings which the motion model uses.
+    pub fn motion(&self) -> PitchMotion {
+        PitchMotion { offset_range: self.offset_range.clone(), speed: self.speed, auto_recenter: self.auto_recenter }
+    }
+}
+
+impl Default for PitchSettings {
+    fn default() -> Self { PitchSettings::new() }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use bevy::core::FixedTimestep;
+    use bevy::ecs::{Stage, System};
+    use bevy::input::mouse::MouseScrollUnit;
+    use bevy::render::camera::CameraProjection;
+    use bevy::window::WindowId;
+
+    /// The time step of a frame at 60 frames per second.
+    pub(crate) const DELTA: f32 = 1.0 / 60.0;
+
+    const VIEWPORT: Vec2 = Vec2 { x: 1280.0, y: 720.0 };
+
+    /// Owned settings of each kind.
+    #[derive(Default)]
+    pub(crate) struct TestSettings {
+        pub(crate) zoom: ZoomSettings,
+        pub(crate) pan: PanSettings,
+        pub(crate) turn: TurnSettings,
+        pub(crate) pitch: PitchSettings,
+    }
+
+    impl TestSettings {
+        fn camera(&self) -> CameraSettings<'_> {
+            CameraSettings { zoom: &self.zoom, pan: &self.pan, turn: &self.turn, pitch: &self.pitch }
+        }
+
+        pub(crate) fn motion(&self) -> MotionSettings<'_> {
+            self.camera().motion()
+        }
+    }
+
+    /// A keyboard with the given keys held, which were all pressed this frame.
+    fn keys(pressed: &[KeyCode]) -> Input<KeyCode> {
+        let mut keyboard = Input::default();
+
+        for key in pressed {
+            keyboard.press(*key);
+        }
+
+        keyboard
+    }
+
+    /// A primary window the size of [`VIEWPORT`], with the cursor at the given position.
+    pub(crate) fn window(cursor: Option<Vec2>) -> Window {
+        let descriptor = WindowDescriptor::default();
+        let mut window = Window::new(WindowId::primary(), &descriptor, VIEWPORT.x as u32, VIEWPORT.y as u32, 1.0);
+        window.update_cursor_position_from_backend(cursor);
+        window
+    }
+
+    /// A camera with the default perspective projection, viewing the whole window.
+    fn perspective_camera() -> Camera {
+        let projection = PerspectiveProjection { aspect_ratio: VIEWPORT.x / VIEWPORT.y, ..Default::default() };
+        Camera { projection_matrix: projection.get_projection_matrix(), ..Default::default() }
+    }
+
+    /// Advance the camera by a frame with the given keys held and no mouse input.
+    fn tick(camera: &mut RtsCamera, settings: &TestSettings, keyboard: &Input<KeyCode>) {
+        camera.tick(None, None, VIEWPORT, keyboard, &settings.camera(), None, DELTA, 0.0);
+    }
+
+    /// The label of the fixed timestep with which [`TestApp`] steps the camera.
+    const FIXED_LABEL: &str = "camera_test";
+
+    /// A world with the resources which the camera system requires, headless unless a window is
+    /// added, which runs the system once per update.
+    pub(crate) struct TestApp {
+        pub(crate) world: World,
+        pub(crate) resources: Resources,
+        stage: SystemStage,
+    }
+
+    impl TestApp {
+        pub(crate) fn new() -> Self {
+            let mut world = World::new();
+            let mut resources = Resources::default();
+            resources.insert(Time::default());
+            resources.insert(Windows::default());
+            resources.insert(Events::<MouseWheel>::default());
+            resources.insert(Events::<MouseMotion>::default());
+            resources.insert(Events::<WindowFocused>::default());
+            resources.insert(Events::<FollowTargetLost>::default());
+            resources.insert(Input::<KeyCode>::default());
+            resources.insert(RtsCameraDefaults::default());
+            resources.insert(FixedTimesteps::default());
+
+            // The frame time is not deterministic, so the camera is stepped by a fixed timestep
+            resources.insert(CameraTimestep::Fixed(FIXED_LABEL.into()));
+            FixedTimestep::step(DELTA as f64).with_label(FIXED_LABEL).initialize(&mut world, &mut resources);
+
+            let stage = SystemStage::serial().with_system(rts_camera_system.system());
+            TestApp { world, resources, stage }
+        }
+
+        /// Run the camera system for a frame. Change detection then reflects this frame, and keys
+        /// pressed on the keyboard resource stay held but are no longer just pressed.
+        pub(crate) fn update(&mut self) {
+            self.world.clear_trackers();
+            self.stage.initialize(&mut self.world, &mut self.resources);
+            self.stage.run(&mut self.world, &mut self.resources);
+            self.resources.get_mut::<Input<KeyCode>>().unwrap().update();
+        }
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-4, "expected {}, got {}", expected, actual);
+    }
+    #[test]
+    fn reversing_uses_the_reverse_deceleration() {
+        let mut settings = TestSettings::default();
+        settings.pan.reverse_deceleration = Some(50.0);
+        let accel = settings.pan.keyboard_accel * DELTA;
+
+        let mut camera = RtsCamera { pan_velocity: Vec2::new(5.0, 0.0), ..Default::default() };
+        tick(&mut camera, &settings, &keys(&[KeyCode::Left]));
+        assert_close(camera.pan_velocity.x, 5.0 - accel - 50.0 * DELTA);
+
+        // Releasing the keys coasts with the idle deceleration
+        let mut camera = RtsCamera { pan_velocity: Vec2::new(5.0, 0.0), ..Default::default() };
+        tick(&mut camera, &settings, &keys(&[]));
+        assert_close(camera.pan_velocity.x, 5.0 - settings.pan.idle_deceleration * DELTA);
+    }
+
+    #[test]
+    fn pitch_recenters_when_idle() {
+        let mut settings = TestSettings::default();
+        settings.pitch.up_keys = Cow::Borrowed(&[KeyCode::R]);
+        settings.pitch.auto_recenter = Some(0.5);
+
+        let mut camera = RtsCamera::default();
+        for _ in 0..30 {
+            tick(&mut camera, &settings, &keys(&[KeyCode::R]));
+        }
+
+        let tilted = camera.pitch_offset;
+        assert_close(tilted, settings.pitch.speed * 30.0 * DELTA);
+
+        tick(&mut camera, &settings, &keys(&[]));
+        assert_close(camera.pitch_offset, tilted - 0.5 * DELTA);
+
+        for _ in 0..60 {
+            tick(&mut camera, &settings, &keys(&[]));
+        }
+
+        // Back at the pitch derived from the zoom distance
+        let zoom = &settings.zoom;
+        let zoom_pitch = lerp_in_zone(camera.zoom_distance, &zoom.angle_change_zone, &zoom.angle_range);
+        assert_eq!(camera.pitch_offset, 0.0);
+        assert!(camera.rotation.abs_diff_eq(Quat::from_rotation_ypr(camera.yaw, -zoom_pitch, 0.0), 1e-5));
+    }
+
+    #[test]
+    fn zoom_level_tracks_the_zoom_fraction() {
+        let mut app = TestApp::new();
+        let camera = RtsCamera { zoom_distance: 52.5, ..Default::default() };
+        let entity = app.world.spawn((camera, Transform::default(), ZoomLevel::default()));
+        let changed = |app: &TestApp| app.world.query_filtered::<Entity, Changed<ZoomLevel>>().count();
+
+        app.update();
+        let range = ZoomSettings::default().distance_range;
+        let expected = (52.5 - range.start()) / (range.end() - range.start());
+        assert_eq!(app.world.get::<ZoomLevel>(entity).unwrap().0, expected);
+        assert_eq!(changed(&app), 1);
+
+        // Only marked as changed when the zoom level changes
+        app.update();
+        assert_eq!(changed(&app), 0);
+
+        app.world.get_mut::<RtsCamera>(entity).unwrap().zoom_distance = 100.0;
+        app.update();
+        assert_eq!(app.world.get::<ZoomLevel>(entity).unwrap().0, 1.0);
+        assert_eq!(changed(&app), 1);
+
+        // A range with equal ends gives a zoom level of 0 rather than NaN
+        let zoom = ZoomSettings { distance_range: 50.0..=50.0, ..Default::default() };
+        app.world.insert_one(entity, zoom).unwrap();
+        app.update();
+        assert_eq!(app.world.get::<ZoomLevel>(entity).unwrap().0, 0.0);
+    }
+
+    #[test]
+    fn input_is_ignored_unless_wanted() {
+        let settings = TestSettings::default();
+        let mut camera = RtsCamera { wants_input: false, ..Default::default() };
+        let keyboard = keys(&[KeyCode::Left, KeyCode::Equals, KeyCode::Q]);
+        let scroll = Some(Vec2::new(0.0, 1.0));
+        let cursor = Some(Vec2::new(0.0, VIEWPORT.y / 2.0));
+
+        camera.tick(scroll, cursor, VIEWPORT, &keyboard, &settings.camera(), None, DELTA, 1.0);
+        assert_eq!(camera.pan_velocity, Vec2::zero());
+        assert_eq!(camera.zoom_velocity, 0.0);
+        assert_eq!(camera.turn_velocity, 0.0);
+        assert_eq!(camera.last_scroll_sec, f64::NEG_INFINITY);
+
+        camera.wants_input = true;
+        camera.tick(scroll, cursor, VIEWPORT, &keyboard, &settings.camera(), None, DELTA, 1.0);
+        assert!(camera.pan_velocity.x < 0.0);
+        assert_eq!(camera.last_scroll_sec, 1.0);
+    }
+
+    #[test]
+    fn sensitivity_scales_the_acceleration() {
+        let velocity_after_press = |settings: &TestSettings| {
+            let mut camera = RtsCamera::default();
+            let keyboard = keys(&[KeyCode::Right, KeyCode::Q]);
+            let scroll = Some(Vec2::new(0.0, -1.0));
+            camera.tick(scroll, None, VIEWPORT, &keyboard, &settings.camera(), None, DELTA, 0.0);
+            (camera.pan_velocity, camera.zoom_velocity, camera.turn_velocity)
+        };
+
+        let mut settings = TestSettings::default();
+        settings.zoom.idle_deceleration = 0.0;
+        let (pan, zoom, turn) = velocity_after_press(&settings);
+        assert!(pan.x > 0.0 && zoom > 0.0 && turn > 0.0);
+
+        settings.pan.sensitivity = Vec2::new(0.5, 1.0);
+        settings.zoom.sensitivity = 0.5;
+        settings.turn.sensitivity = 0.5;
+        let (half_pan, half_zoom, half_turn) = velocity_after_press(&settings);
+
+        assert_close(half_pan.x, pan.x / 2.0);
+        assert_close(half_zoom, zoom / 2.0);
+        assert_close(half_turn, turn / 2.0);
+    }
+
+    #[test]
+    fn cameras_without_settings_use_the_defaults() {
+        let mut app = TestApp::new();
+        let camera = || RtsCamera { zoom_distance: 50.0, ..Default::default() };
+        let with_settings = app.world.spawn((camera(), Transform::default(), ZoomSettings::default()));
+        let without = app.world.spawn((camera(), Transform::default()));
+
+        app.resources.get_mut::<RtsCameraDefaults>().unwrap().zoom.distance_range = 5.0..=20.0;
+        app.update();
+
+        assert_eq!(app.world.get::<RtsCamera>(without).unwrap().zoom_distance, 20.0);
+        assert_eq!(app.world.get::<RtsCamera>(with_settings).unwrap().zoom_distance, 50.0);
+    }
+
+    #[test]
+    fn idle_cinematic_starts_after_the_delay_and_stops_on_input() {
+        let settings = TestSettings::default();
+        let cinematic = IdleCinematic { delay: 1.0, pan_speed: 2.0, turn_speed: 0.25 };
+        let mut camera = RtsCamera::default();
+        let tick = |camera: &mut RtsCamera, keyboard: &Input<KeyCode>| {
+            camera.tick(None, None, VIEWPORT, keyboard, &settings.camera(), Some(&cinematic), DELTA, 0.0);
+        };
+
+        for _ in 0..59 {
+            tick(&mut camera, &keys(&[]));
+        }
+
+        assert_eq!(camera.pan_velocity, Vec2::zero());
+        assert_eq!(camera.turn_velocity, 0.0);
+
+        for _ in 0..2 {
+            tick(&mut camera, &keys(&[]));
+        }
+
+        assert_close(camera.pan_velocity.x, 2.0);
+        assert_close(camera.turn_velocity, 0.25);
+        let moving = camera.looking_at;
+        tick(&mut camera, &keys(&[]));
+        assert!(camera.looking_at.x > moving.x);
+
+        // Any input returns control, after which the camera decelerates as usual
+        tick(&mut camera, &keys(&[KeyCode::Down]));
+        assert_eq!(camera.idle_secs, 0.0);
+        let released = camera.pan_velocity.x;
+        tick(&mut camera, &keys(&[]));
+        assert_close(camera.pan_velocity.x, released - settings.pan.idle_deceleration * DELTA);
+        assert!(camera.turn_velocity < 0.25);
+    }
+
+    #[test]
+    fn runs_headless_with_keyboard_input() {
+        let mut app = TestApp::new();
+        let camera = app.world.spawn((RtsCamera::default(), Transform::default(), ViewportSize(VIEWPORT)));
+        app.resources.get_mut::<Input<KeyCode>>().unwrap().press(KeyCode::Right);
+
+        for _ in 0..10 {
+            app.update();
+        }
+
+        let camera = app.world.get::<RtsCamera>(camera).unwrap();
+        assert!(camera.looking_at.x > 0.0);
+        assert_eq!(camera.looking_at.z, 0.0);
+    }
+
+    #[test]
+    fn clip_planes_follow_the_zoom_distance() {
+        let mut app = TestApp::new();
+        let range = ZoomSettings::default().distance_range;
+        let clip_adjust = ClipAdjust { near_range: 0.1..=1.0, far_range: 100.0..=1000.0 };
+        let camera = RtsCamera { zoom_distance: *range.start(), ..Default::default() };
+        let entity = app.world.spawn((camera, Transform::default(), clip_adjust, PerspectiveProjection::default()));
+
+        app.update();
+        let projection = app.world.get::<PerspectiveProjection>(entity).unwrap();
+        assert_close(projection.near, 0.1);
+        assert_close(projection.far, 100.0);
+
+        app.world.get_mut::<RtsCamera>(entity).unwrap().zoom_distance = *range.end();
+        app.update();
+        let projection = app.world.get::<PerspectiveProjection>(entity).unwrap();
+        assert_close(projection.near, 1.0);
+        assert_close(projection.far, 1000.0);
+
+        // A fixed zoom distance uses the start of each range rather than NaN
+        let zoom = ZoomSettings { distance_range: 50.0..=50.0, ..Default::default() };
+        app.world.insert_one(entity, zoom).unwrap();
+        app.update();
+        let projection = app.world.get::<PerspectiveProjection>(entity).unwrap();
+        assert_eq!((projection.near, projection.far), (0.1, 100.0));
+    }
+
+    #[test]
+    fn pixel_snap_quantizes_only_the_written_transform() {
+        let mut app = TestApp::new();
+        let looking_at = Vec3::new(0.3, 0.0, 0.7);
+        let camera = RtsCamera { looking_at, ..Default::default() };
+        let entity = app.world.spawn((camera, Transform::default(), PixelSnap { units_per_pixel: 0.5 }));
+
+        app.update();
+        let translation = app.world.get::<Transform>(entity).unwrap().translation;
+        for coordinate in &[translation.x, translation.y, translation.z] {
+            assert_eq!((coordinate / 0.5).fract(), 0.0);
+        }
+
+        let camera = app.world.get::<RtsCamera>(entity).unwrap();
+        assert!(camera.looking_at.abs_diff_eq(looking_at, 1e-5));
+        assert!((camera.camera_translation() - translation).length() > 0.0);
+    }
+
+    #[test]
+    fn pan_magnitude_scales_the_acceleration() {
+        let settings = TestSettings::default();
+        let velocity_with = |right: f32| {
+            let pan_input = Some(PanMagnitudes { right, ..Default::default() });
+            let mut camera = RtsCamera { pan_input, ..Default::default() };
+            tick(&mut camera, &settings, &keys(&[]));
+            camera.pan_velocity.x
+        };
+
+        let full = velocity_with(1.0);
+        assert!(full > 0.0);
+        assert_close(velocity_with(0.5), full / 2.0);
+
+        // Keys have a magnitude of 1
+        let mut camera = RtsCamera::default();
+        tick(&mut camera, &settings, &keys(&[KeyCode::Right]));
+        assert_eq!(camera.pan_velocity.x, full);
+    }
+
+    #[test]
+    fn settings_overrides_apply_until_popped() {
+        let mut app = TestApp::new();
+        let entity = app.world.spawn((RtsCamera::default(), Transform::default(), PanSettings::default()));
+        app.resources.get_mut::<Input<KeyCode>>().unwrap().press(KeyCode::Right);
+        let pan_velocity = |app: &mut TestApp| {
+            app.world.get_mut::<RtsCamera>(entity).unwrap().pan_velocity = Vec2::zero();
+            app.update();
+            app.world.get::<RtsCamera>(entity).unwrap().pan_velocity.x
+        };
+
+        let base = pan_velocity(&mut app);
+
+        let mut slow = PanSettings::default();
+        slow.keyboard_accel /= 4.0;
+        let slow = SettingsOverride { pan: Some(slow), ..Default::default() };
+        app.world.get_mut::<RtsCamera>(entity).unwrap().push_settings_override(slow.clone());
+        assert_close(pan_velocity(&mut app), base / 4.0);
+
+        let popped = app.world.get_mut::<RtsCamera>(entity).unwrap().pop_settings_override();
+        assert_eq!(popped, Some(slow));
+        assert_eq!(pan_velocity(&mut app), base);
+    }
+
+    #[test]
+    fn turn_margin_is_measured_from_the_configured_edge() {
+        // The cursor origin is at the bottom left, and the default margin is a quarter of the height
+        let mut turn = TurnSettings::default();
+        assert!(turn.in_mouse_turn_margin(Vec2::new(0.0, 541.0), VIEWPORT));
+        assert!(!turn.in_mouse_turn_margin(Vec2::new(0.0, 540.0), VIEWPORT));
+        assert!(!turn.in_mouse_turn_margin(Vec2::new(0.0, 10.0), VIEWPORT));
+
+        turn.mouse_turn_edge = VerticalEdge::Bottom;
+        assert!(turn.in_mouse_turn_margin(Vec2::new(0.0, 179.0), VIEWPORT));
+        assert!(!turn.in_mouse_turn_margin(Vec2::new(0.0, 180.0), VIEWPORT));
+        assert!(!turn.in_mouse_turn_margin(Vec2::new(0.0, 710.0), VIEWPORT));
+    }
+
+    #[test]
+    fn idle_time_counts_up_and_resets_on_input() {
+        let settings = TestSettings::default();
+        let mut camera = RtsCamera::default();
+
+        for _ in 0..3 {
+            tick(&mut camera, &settings, &keys(&[]));
+        }
+
+        assert_close(camera.idle_secs(), 3.0 * DELTA);
+
+        for _ in 0..30 {
+            tick(&mut camera, &settings, &keys(&[KeyCode::Q]));
+        }
+
+        assert_eq!(camera.idle_secs(), 0.0);
+
+        // Coasting after input is released counts as idle
+        tick(&mut camera, &settings, &keys(&[]));
+        assert!(camera.turn_velocity != 0.0);
+        assert_close(camera.idle_secs(), DELTA);
+    }
+
+    #[test]
+    fn far_plane_is_extended_to_see_the_ground() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(RtsCameraDefaults::default());
+
+        let mut zoom = ZoomSettings { distance_range: 5.0..=5000.0, ..Default::default() };
+        let projection = PerspectiveProjection::default();
+        let required_far = zoom.required_far_plane(projection.fov);
+        assert!(projection.far < required_far);
+
+        let warned = world.spawn((RtsCamera::default(), zoom.clone(), projection.clone()));
+        zoom.auto_extend_far_plane = true;
+        let extended = world.spawn((RtsCamera::default(), zoom, projection.clone()));
+
+        let mut stage = SystemStage::serial().with_system(far_plane_validation_system.system());
+        stage.initialize(&mut world, &mut resources);
+        stage.run(&mut world, &mut resources);
+
+        assert_eq!(world.get::<PerspectiveProjection>(warned).unwrap().far, projection.far);
+        assert_eq!(world.get::<PerspectiveProjection>(extended).unwrap().far, required_far);
+    }
+
+    #[test]
+    fn hitting_the_bounds_bumps_the_rendered_transform() {
+        let mut app = TestApp::new();
+        let mut bounds = PanBounds::new(Vec2::new(-10.0, -10.0), Vec2::new(10.0, 10.0));
+        bounds.bump = 0.05;
+        let looking_at = Vec3::new(9.99, 0.0, 0.0);
+        let camera = RtsCamera { looking_at, pan_velocity: Vec2::new(5.0, 0.0), ..Default::default() };
+        let entity = app.world.spawn((camera, Transform::default(), bounds));
+
+        app.update();
+        let camera = app.world.get::<RtsCamera>(entity).unwrap();
+        let offset = app.world.get::<Transform>(entity).unwrap().translation - camera.camera_translation();
+        assert_eq!(camera.looking_at.x, 10.0);
+        assert_eq!(camera.pan_velocity.x, 0.0);
+        assert!(offset.x > 0.0);
+        assert!(offset.abs_diff_eq(camera.render_offset, 1e-5));
+
+        for _ in 0..60 {
+            app.update();
+        }
+
+        let camera = app.world.get::<RtsCamera>(entity).unwrap();
+        let offset = app.world.get::<Transform>(entity).unwrap().translation - camera.camera_translation();
+        assert_eq!(camera.looking_at.x, 10.0);
+        assert!(offset.length() < 1e-4);
+    }
+
+    #[test]
+    fn distance_and_pitch_change_in_their_own_zones() {
+        let zoom = ZoomSettings {
+            distance_range: 5.0..=100.0,
+            angle_change_zone: 5.0..=20.0,
+            distance_change_zone: Some(20.0..=100.0),
+            ..Default::default()
+        };
+
+        // Close in, only the pitch changes
+        assert_eq!(zoom.camera_distance_at(5.0), 20.0);
+        assert_eq!(zoom.camera_distance_at(15.0), 20.0);
+        assert!(zoom.pitch_at(5.0) != zoom.pitch_at(15.0));
+
+        // Further out, only the distance changes
+        assert_eq!(zoom.pitch_at(30.0), zoom.pitch_at(90.0));
+        assert_eq!(zoom.camera_distance_at(30.0), 30.0);
+        assert_eq!(zoom.camera_distance_at(90.0), 90.0);
+    }
+
+    #[test]
+    fn focusing_on_a_selection_frames_its_centroid() {
+        let settings = TestSettings::default();
+        let (window, camera_component) = (window(None), perspective_camera());
+        let points = [Vec3::new(20.0, 0.0, 0.0), Vec3::new(40.0, 0.0, 10.0), Vec3::new(30.0, 2.0, -40.0)];
+        let centroid = Vec3::new(30.0, 2.0 / 3.0, -10.0);
+
+        let mut camera = RtsCamera::default();
+        camera.focus_on_centroid(&[]);
+        assert!(camera.tween.is_none());
+
+        camera.focus_on_centroid(&points[..1]);
+        assert_eq!(camera.tween.unwrap().to.looking_at, points[0]);
+
+        camera.focus_on_centroid_framed(&points, &camera_component, &settings.zoom);
+        let target = camera.tween.unwrap().to;
+        assert!(target.looking_at.abs_diff_eq(centroid, 1e-5));
+        assert!(target.zoom_distance > camera.zoom_distance);
+
+        while camera.tween.is_some() {
+            tick(&mut camera, &settings, &keys(&[]));
+        }
+
+        for point in &points {
+            assert!(camera.is_on_screen(*point, &window, &camera_component));
+        }
+    }
+
+    #[test]
+    fn edge_and_keyboard_pan_combine_by_the_policy() {
+        let pan_accel = |combine: InputCombine, cursor: Option<Vec2>, keyboard: &Input<KeyCode>| {
+            let mut settings = TestSettings::default();
+            settings.pan.input_combine = combine;
+            let mut camera = RtsCamera::default();
+            let intent = camera.read_intent(None, cursor, VIEWPORT, keyboard, &settings.camera(), DELTA, 0.0);
+            intent.pan_accel.x
+        };
+
+        let (edge, no_edge) = (Some(Vec2::new(VIEWPORT.x - 1.0, VIEWPORT.y / 2.0)), None);
+        let (right, no_keys) = (keys(&[KeyCode::Right]), keys(&[]));
+        let edge_only = pan_accel(InputCombine::Sum, edge, &no_keys);
+        let key_only = pan_accel(InputCombine::Sum, no_edge, &right);
+        assert!(edge_only > 0.0 && key_only > 0.0 && edge_only != key_only);
+
+        assert_close(pan_accel(InputCombine::Sum, edge, &right), edge_only + key_only);
+        assert_eq!(pan_accel(InputCombine::Max, edge, &right), edge_only.max(key_only));
+        assert_eq!(pan_accel(InputCombine::Priority(InputSource::MouseEdge), edge, &right), edge_only);
+        assert_eq!(pan_accel(InputCombine::Priority(InputSource::Keyboard), edge, &right), key_only);
+
+        // The lower priority source is used when the other has no input
+        assert_eq!(pan_accel(InputCombine::Priority(InputSource::Keyboard), edge, &no_keys), edge_only);
+    }
+
+    #[test]
+    fn steady_scrolling_does_not_decelerate_between_events() {
+        let settings = TestSettings::default();
+        let mut camera = RtsCamera::default();
+        let no_keys = keys(&[]);
+        let undecelerated = Deceleration { pos: false, neg: false };
+        let mut intent_at = |frame: u32, scroll: Option<Vec2>| {
+            let now = frame as f64 * DELTA as f64;
+            camera.read_intent(scroll, None, VIEWPORT, &no_keys, &settings.camera(), DELTA, now)
+        };
+
+        // Scrolling every 9 frames, which is longer than the minimum grace period
+        assert!(9.0 * DELTA as f64 > *settings.zoom.scroll_grace_secs.start());
+        for frame in 0..90 {
+            let scroll = if frame % 9 == 0 { Some(Vec2::new(0.0, 1.0)) } else { None };
+            let intent = intent_at(frame, scroll);
+
+            if frame > 9 {
+                assert_eq!(intent.zoom_decel, undecelerated, "decelerated at frame {}", frame);
+            }
+        }
+
+        // Once scrolling stops, the camera decelerates again
+        assert_eq!(intent_at(120, None).zoom_decel, Deceleration::default());
+    }
+
+    #[test]
+    fn cursor_ray_starts_at_the_camera() {
+        let settings = TestSettings::default();
+        let mut camera = RtsCamera { looking_at: Vec3::new(5.0, 0.0, -3.0), ..Default::default() };
+        tick(&mut camera, &settings, &keys(&[]));
+        let (window, camera_component) = (window(None), perspective_camera());
+
+        // Through the centre of the screen, the ray heads straight for the target
+        let centre = VIEWPORT / 2.0;
+        let (origin, direction) = camera.cursor_ray(centre, &window, &camera_component).unwrap();
+        let eye = camera.camera_translation();
+        assert!(origin.abs_diff_eq(eye, 1e-3));
+        assert!(direction.abs_diff_eq((camera.looking_at - eye).normalize(), 1e-4));
+        assert_close(direction.length(), 1.0);
+
+        // A cursor below the centre points further down, towards the camera
+        let (_, lower) = camera.cursor_ray(Vec2::new(centre.x, 100.0), &window, &camera_component).unwrap();
+        assert!(lower.y < direction.y);
+        assert!(camera.cursor_to_ground(Vec2::new(centre.x, 100.0), &window, &camera_component).unwrap().z > -3.0);
+    }
+
+    #[test]
+    fn scroll_smoothing_ramps_the_zoom_velocity() {
+        let zoom_velocities = |smoothing: f32| {
+            let mut settings = TestSettings::default();
+            settings.zoom.idle_deceleration = 0.0;
+            settings.zoom.scroll_smoothing = smoothing;
+            let mut camera = RtsCamera::default();
+            let mut velocities = Vec::new();
+
+            for frame in 0..12 {
+                let scroll = if frame == 0 { Some(Vec2::new(0.0, -1.0)) } else { None };
+                let now = frame as f64 * DELTA as f64;
+                camera.tick(scroll, None, VIEWPORT, &keys(&[]), &settings.camera(), None, DELTA, now);
+                velocities.push(camera.zoom_velocity);
+            }
+
+            velocities
+        };
+
+        let instant = zoom_velocities(0.0);
+        assert!(instant[0] > 0.0);
+        assert!(instant.iter().all(|velocity| *velocity == instant[0]));
+
+        // The same impulse is spread over the smoothing time
+        let smoothed = zoom_velocities(0.1);
+        assert!(smoothed[0] > 0.0 && smoothed[0] < instant[0] / 2.0);
+        assert!(smoothed.windows(2).take(5).all(|pair| pair[1] > pair[0]));
+        assert_close(*smoothed.last().unwrap(), instant[0]);
+    }
+
+    #[test]
+    fn acquiring_the_camera_blocks_player_input() {
+        let settings = TestSettings::default();
+        let mut camera = RtsCamera::default();
+        assert!(camera.acquire(10));
+
+        // A scripted move at the held priority runs, and blocks player pan input
+        let target = CameraState { looking_at: Vec3::new(0.0, 0.0, -20.0), ..camera.state() };
+        assert!(camera.start_tween(Tween::new(camera.state(), target, 0.5).with_priority(10)));
+        assert!(!camera.start_tween(Tween::new(camera.state(), target, 0.5)));
+        assert!(!camera.acquire(5));
+
+        for _ in 0..60 {
+            tick(&mut camera, &settings, &keys(&[KeyCode::Right]));
+        }
+
+        assert_eq!(camera.pan_velocity, Vec2::zero());
+        assert!(camera.looking_at.abs_diff_eq(target.looking_at, 1e-4));
+
+        camera.release();
+        tick(&mut camera, &settings, &keys(&[KeyCode::Right]));
+        assert!(camera.pan_velocity.x > 0.0);
+    }
+
+    #[test]
+    fn key_and_edge_turning_combine_before_decelerating() {
+        let settings = TestSettings::default();
+        let turn_intent = |keyboard: &Input<KeyCode>| {
+            let mut camera = RtsCamera::default();
+            let cursor = Some(Vec2::new(1.0, 600.0));
+            let intent = camera.read_intent(None, cursor, VIEWPORT, keyboard, &settings.camera(), DELTA, 0.0);
+            (intent.turn_accel, intent.turn_decel)
+        };
+        let (edge, key) = (settings.turn.mouse_accel * DELTA, settings.turn.keyboard_accel * DELTA);
+
+        // Both turning left add up, and only the deceleration against turning left stops
+        let (accel, decel) = turn_intent(&keys(&[KeyCode::Q]));
+        assert_close(accel, edge + key);
+        assert_eq!(decel, Deceleration { pos: false, neg: true });
+
+        // Opposing sources net out, stopping only the deceleration against the net direction
+        let (accel, decel) = turn_intent(&keys(&[KeyCode::E]));
+        assert_close(accel, edge - key);
+        assert_eq!(decel, Deceleration { pos: true, neg: false });
+
+        // The camera then turns in the net direction
+        let mut camera = RtsCamera::default();
+        let cursor = Some(Vec2::new(1.0, 600.0));
+        camera.tick(None, cursor, VIEWPORT, &keys(&[KeyCode::E]), &settings.camera(), None, DELTA, 0.0);
+        assert!(camera.turn_velocity < 0.0);
+    }
+
+    #[test]
+    fn camera_keeps_its_clearance_over_a_hill() {
+        let mut app = TestApp::new();
+        // A plateau behind the target, under the camera
+        let terrain = Terrain::new(|point: Vec2| if point.y > 1.0 { 100.0 } else { 0.0 }, 2.0);
+        let entity = app.world.spawn((RtsCamera::default(), Transform::default(), terrain));
+
+        app.update();
+        let eye = app.world.get::<Transform>(entity).unwrap().translation;
+        let camera = app.world.get::<RtsCamera>(entity).unwrap();
+        assert!(eye.z > 1.0);
+        assert_close(eye.y, 102.0);
+        assert!(camera.camera_distance > camera.zoom_distance);
+        assert_eq!(camera.looking_at, Vec3::zero());
+    }
+
+    #[test]
+    fn restoring_a_snapshot_replays_identically() {
+        let settings = TestSettings::default();
+        let step = |camera: &mut RtsCamera, frame: u32, keyboard: &Input<KeyCode>| {
+            let scroll = if frame == 0 { Some(Vec2::new(0.0, 1.0)) } else { None };
+            let now = frame as f64 * DELTA as f64;
+            camera.tick(scroll, None, VIEWPORT, keyboard, &settings.camera(), None, DELTA, now);
+        };
+        let replay = |camera: &mut RtsCamera| {
+            (20..50)
+                .map(|frame| {
+                    step(camera, frame, &keys(&[]));
+                    (camera.snapshot(), camera.rotation)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut camera = RtsCamera::default();
+        for frame in 0..20 {
+            step(&mut camera, frame, &keys(&[KeyCode::Right, KeyCode::Q]));
+        }
+
+        let snapshot = camera.snapshot();
+        assert!(snapshot.pan_velocity != Vec2::zero() && snapshot.turn_velocity != 0.0);
+        assert!(snapshot.last_scroll_sec.is_some());
+        let original = replay(&mut camera);
+
+        camera.looking_at = Vec3::new(100.0, 0.0, 100.0);
+        camera.pan_velocity = Vec2::zero();
+        camera.turn_velocity = -1.0;
+        camera.zoom_distance = 80.0;
+        camera.rotation = Quat::default();
+        camera.restore(&snapshot);
+        assert_eq!(camera.snapshot(), snapshot);
+        assert_eq!(replay(&mut camera), original);
+    }
+
+    #[test]
+    fn tilt_toward_the_cursor_is_bounded_and_vanishes_at_the_centre() {
+        let mut app = TestApp::new();
+        app.resources.get_mut::<Windows>().unwrap().add(window(Some(Vec2::new(-100.0, -100.0))));
+        let camera = RtsCamera { tilt_toward_cursor: 0.05, wants_input: false, ..Default::default() };
+        let entity = app.world.spawn((camera, Transform::default()));
+        let lean = |app: &TestApp| {
+            let rendered = app.world.get::<Transform>(entity).unwrap().rotation;
+            let logical = app.world.get::<RtsCamera>(entity).unwrap().camera_transform().rotation;
+            (rendered * logical.conjugate()).to_axis_angle().1
+        };
+
+        for _ in 0..120 {
+            app.update();
+        }
+
+        // Past the corner, the lean is clamped to the maximum on both axes
+        let tilt = app.world.get::<RtsCamera>(entity).unwrap().render_tilt;
+        assert!(tilt.abs_diff_eq(Vec2::new(-0.05, -0.05), 1e-4));
+        assert!(lean(&app) > 0.0 && lean(&app) < 0.1);
+
+        let centre = VIEWPORT / 2.0;
+        let mut windows = app.resources.get_mut::<Windows>().unwrap();
+        windows.get_primary_mut().unwrap().update_cursor_position_from_backend(Some(centre));
+        drop(windows);
+
+        for _ in 0..120 {
+            app.update();
+        }
+
+        assert!(lean(&app) < 1e-4);
+    }
+
+    #[test]
+    fn zoom_axis_scales_the_acceleration() {
+        let settings = TestSettings::default();
+        let zoom_accel = |zoom_input: Option<f32>, keyboard: &Input<KeyCode>| {
+            let mut camera = RtsCamera { zoom_input, ..Default::default() };
+            camera.read_intent(None, None, VIEWPORT, keyboard, &settings.camera(), DELTA, 0.0).zoom_accel
+        };
+
+        let zoom_out = zoom_accel(None, &keys(&[KeyCode::Minus]));
+        assert_close(zoom_out, settings.zoom.keyboard_accel * DELTA);
+        assert_close(zoom_accel(Some(0.5), &keys(&[])), zoom_out / 2.0);
+        assert_close(zoom_accel(Some(-0.5), &keys(&[])), -zoom_out / 2.0);
+
+        // The axis overrides the keys, and is limited to a magnitude of 1
+        assert_eq!(zoom_accel(Some(0.0), &keys(&[KeyCode::Minus])), 0.0);
+        assert_close(zoom_accel(Some(3.0), &keys(&[])), zoom_out);
+    }
+
+    #[test]
+    fn fixed_timestep_steps_deterministically() {
+        let app = TestApp::new();
+        let time = app.resources.get::<Time>().unwrap();
+        let fixed_timesteps = app.resources.get::<FixedTimesteps>().unwrap();
+        assert_eq!(CameraTimestep::Fixed(FIXED_LABEL.into()).delta(&time, &fixed_timesteps), DELTA);
+        assert_eq!(CameraTimestep::Fixed("missing".into()).delta(&time, &fixed_timesteps), time.delta_seconds());
+        assert_eq!(CameraTimestep::Frame.delta(&time, &fixed_timesteps), time.delta_seconds());
+        drop((time, fixed_timesteps));
+
+        let run = |mut app: TestApp| {
+            let entity = app.world.spawn((RtsCamera::default(), Transform::default()));
+            app.resources.get_mut::<Input<KeyCode>>().unwrap().press(KeyCode::Up);
+            app.resources.get_mut::<Input<KeyCode>>().unwrap().press(KeyCode::E);
+
+            for _ in 0..30 {
+                app.update();
+            }
+
+            let transform = *app.world.get::<Transform>(entity).unwrap();
+            (app.world.get::<RtsCamera>(entity).unwrap().snapshot(), transform)
+        };
+
+        // The frame time is zero as it is never updated, so the camera only moves by the fixed step
+        let (snapshot, transform) = run(app);
+        assert!(snapshot.looking_at.z < 0.0 && snapshot.yaw != 0.0);
+        assert_eq!(run(TestApp::new()), (snapshot, transform));
+    }
+
+    #[test]
+    fn framing_distance_fits_the_narrower_axis() {
+        let zoom = ZoomSettings { distance_range: 1.0..=1000.0, ..Default::default() };
+        let camera = RtsCamera::default();
+
+        // In a landscape view, the vertical field of view is the narrower
+        let landscape = perspective_camera();
+        let half_fov = PerspectiveProjection::default().fov / 2.0;
+        let distance = camera.distance_to_frame(10.0, &landscape, &zoom);
+        assert_close(distance, 10.0 / half_fov.sin());
+
+        // In a portrait view, the horizontal field of view is the narrower, so it is further away
+        let projection = PerspectiveProjection { aspect_ratio: 0.5, ..Default::default() };
+        let portrait = Camera { projection_matrix: projection.get_projection_matrix(), ..Default::default() };
+        let portrait_distance = camera.distance_to_frame(10.0, &portrait, &zoom);
+        let half_width = (half_fov.tan() * 0.5).atan();
+        assert!(portrait_distance > distance);
+        assert_close(portrait_distance, 10.0 / half_width.sin());
+
+        // The distance is clamped to the zoom range
+        assert_eq!(camera.distance_to_frame(1e6, &landscape, &zoom), 1000.0);
+        assert_eq!(camera.distance_to_frame(0.0, &landscape, &zoom), 1.0);
+    }
+
+    #[test]
+    fn control_flags_disable_only_their_input() {
+        let mut settings = TestSettings::default();
+        settings.pitch.up_keys = Cow::Borrowed(&[KeyCode::R]);
+        let keyboard = keys(&[KeyCode::Right, KeyCode::Minus, KeyCode::Q, KeyCode::R]);
+        // In the top right corner, the cursor turns the camera and pans it up
+        let cursor = Some(Vec2::new(VIEWPORT.x - 1.0, VIEWPORT.y - 5.0));
+        let intent_with = |controls: CameraControls| {
+            let mut camera = RtsCamera { controls, ..Default::default() };
+            let scroll = Some(Vec2::new(0.0, -1.0));
+            camera.read_intent(scroll, cursor, VIEWPORT, &keyboard, &settings.camera(), DELTA, 0.0)
+        };
+
+        let all = intent_with(CameraControls::ALL);
+        assert!(all.pan_accel.x > 0.0 && all.pan_accel.y > 0.0);
+        assert!(all.zoom_accel > settings.zoom.keyboard_accel * DELTA);
+        assert!(all.turn_accel != settings.turn.keyboard_accel * DELTA);
+        assert_eq!(all.pitch, Some(1.0));
+
+        let none = intent_with(CameraControls::NONE);
+        assert_eq!((none.pan_accel, none.zoom_accel, none.turn_accel, none.pitch), (Vec2::zero(), 0.0, 0.0, None));
+
+        let without = |flag| intent_with(CameraControls::ALL - flag);
+        assert_eq!(without(CameraControls::EDGE_PAN).pan_accel, Vec2::new(all.pan_accel.x, 0.0));
+        assert_eq!(without(CameraControls::EDGE_PAN).zoom_accel, all.zoom_accel);
+        assert_eq!(without(CameraControls::PAN).pan_accel, Vec2::zero());
+        assert_eq!(without(CameraControls::PAN).turn_accel, all.turn_accel);
+        assert_close(without(CameraControls::SCROLL_ZOOM).zoom_accel, settings.zoom.keyboard_accel * DELTA);
+        assert_eq!(without(CameraControls::SCROLL_ZOOM).pan_accel, all.pan_accel);
+        assert_eq!(without(CameraControls::ZOOM).zoom_accel, 0.0);
+        assert_eq!(without(CameraControls::ZOOM).turn_accel, all.turn_accel);
+        assert_close(without(CameraControls::MOUSE_TURN).turn_accel, settings.turn.keyboard_accel * DELTA);
+        assert_eq!(without(CameraControls::TURN).turn_accel, 0.0);
+        assert_eq!(without(CameraControls::TURN).zoom_accel, all.zoom_accel);
+        assert_eq!(without(CameraControls::PITCH).pitch, None);
+        assert_eq!(without(CameraControls::PITCH).pan_accel, all.pan_accel);
+    }
+
+    #[test]
+    fn pan_speed_factor_eases_over_the_zoom_distance() {
+        let zoom = ZoomSettings { distance_range: 10.0..=110.0, ..Default::default() };
+        let mut pan = PanSettings { pan_speed_zoom_factor_range: 1.0..=3.0, ..Default::default() };
+
+        // Linearly, the factor tracks the zoom distance rather than the angle change zone
+        assert_close(pan.speed_zoom_factor(35.0, &zoom), 1.5);
+        assert_close(pan.speed_zoom_factor(60.0, &zoom), 2.0);
+
+        pan.pan_speed_zoom_factor_easing = Easing::SmoothStep;
+        assert!(pan.speed_zoom_factor(35.0, &zoom) < 1.5);
+        assert_close(pan.speed_zoom_factor(60.0, &zoom), 2.0);
+        assert!(pan.speed_zoom_factor(85.0, &zoom) > 2.5);
+
+        // A range with equal ends gives the start of the factor range rather than NaN
+        let fixed = ZoomSettings { distance_range: 50.0..=50.0, ..Default::default() };
+        assert_eq!(pan.speed_zoom_factor(50.0, &fixed), 1.0);
+    }
+
+    #[test]
+    fn pan_speed_factor_spans_the_distance_range() {
+        let (zoom, pan) = (ZoomSettings::default(), PanSettings::default());
+        let (min, max) = (*zoom.distance_range.start(), *zoom.distance_range.end());
+        assert_eq!(pan.speed_zoom_factor(min, &zoom), *pan.pan_speed_zoom_factor_range.start());
+        assert_eq!(pan.speed_zoom_factor(max, &zoom), *pan.pan_speed_zoom_factor_range.end());
+
+        // Panning is faster when zoomed out
+        let pan_distance = |zoom_distance: f32| {
+            let settings = TestSettings::default();
+            let mut camera = RtsCamera { zoom_distance, pan_velocity: Vec2::new(1.0, 0.0), ..Default::default() };
+            tick(&mut camera, &settings, &keys(&[KeyCode::Right]));
+            camera.looking_at.x
+        };
+        assert_close(pan_distance(max) / pan_distance(min), pan.speed_zoom_factor(max, &zoom));
+    }
+
+    #[test]
+    fn step_keys_fire_once_per_press() {
+        let mut settings = TestSettings::default();
+        settings.turn.step_left_keys = Cow::Borrowed(&[KeyCode::Z]);
+        settings.turn.step_angle = 0.5;
+        settings.zoom.step_out_keys = Cow::Borrowed(&[KeyCode::X]);
+        settings.zoom.step_distance = 4.0;
+
+        let mut camera = RtsCamera::default();
+        let start_distance = camera.zoom_distance;
+        let mut keyboard = keys(&[KeyCode::Z, KeyCode::X]);
+
+        // Held over several frames, the steps only happen on the frame of the press
+        for _ in 0..10 {
+            tick(&mut camera, &settings, &keyboard);
+            keyboard.update();
+        }
+
+        assert_close(camera.yaw, 0.5);
+        assert_close(camera.zoom_distance, start_distance + 4.0);
+        assert_eq!((camera.turn_velocity, camera.zoom_velocity), (0.0, 0.0));
+
+        keyboard.release(KeyCode::Z);
+        keyboard.update();
+        keyboard.press(KeyCode::Z);
+        tick(&mut camera, &settings, &keyboard);
+        assert_close(camera.yaw, 1.0);
+    }
+
+    #[test]
+    fn parented_cameras_are_offset_by_the_rig() {
+        let mut app = TestApp::new();
+        let rig = Transform::from_translation(Vec3::new(100.0, 5.0, -50.0))
+            * Transform::from_rotation(Quat::from_rotation_y(1.0));
+        let parent = app.world.spawn((GlobalTransform::from(rig),));
+        let camera = || RtsCamera { looking_at: Vec3::new(3.0, 0.0, 4.0), ..Default::default() };
+        let root = app.world.spawn((camera(), Transform::default()));
+        let child = app.world.spawn((camera(), Transform::default(), Parent(parent)));
+
+        app.update();
+        let root_transform = app.world.get::<Transform>(root).unwrap().compute_matrix();
+        let local = app.world.get::<Transform>(child).unwrap().compute_matrix();
+        // The local transform is that of the root camera, so the world transform is offset by the rig
+        assert!(local.abs_diff_eq(root_transform, 1e-5));
+
+        // Picking happens in world space
+        let camera_component = perspective_camera();
+        let centre = app.world.get::<RtsCamera>(child).unwrap().screen_center_ground(&camera_component).unwrap();
+        assert!(centre.abs_diff_eq(rig.mul_vec3(Vec3::new(3.0, 0.0, 4.0)), 1e-3));
+    }
+
+    #[test]
+    fn inverted_angle_is_steepest_when_zoomed_in() {
+        let mut zoom = ZoomSettings::default();
+        let (min, max) = (*zoom.angle_change_zone.start(), *zoom.angle_change_zone.end());
+        assert_eq!(zoom.pitch_at(min), *zoom.angle_range.start());
+        assert_eq!(zoom.pitch_at(max), *zoom.angle_range.end());
+
+        zoom.invert_angle = true;
+        assert_eq!(zoom.pitch_at(min), *zoom.angle_range.end());
+        assert_eq!(zoom.pitch_at(max), *zoom.angle_range.start());
+    }
+
+    #[test]
+    fn reversing_off_a_limit_responds_at_once() {
+        let mut settings = TestSettings::default();
+        // Without idle deceleration, any velocity built up at the limit would delay the reversal
+        settings.zoom.idle_deceleration = 0.0;
+        let min = *settings.zoom.distance_range.start();
+        let mut camera = RtsCamera { zoom_distance: min, zoom_input: Some(-1.0), ..Default::default() };
+
+        for _ in 0..60 {
+            tick(&mut camera, &settings, &keys(&[]));
+            assert_eq!(camera.zoom_distance, min);
+            assert_eq!(camera.zoom_velocity, 0.0);
+        }
+
+        camera.zoom_input = Some(1.0);
+        tick(&mut camera, &settings, &keys(&[]));
+        assert!(camera.zoom_distance > min);
+    }
+
+    #[test]
+    fn input_below_the_deadzone_is_ignored() {
+        let settings = TestSettings::default();
+        let pan_input = Some(PanMagnitudes { right: 0.1, ..Default::default() });
+        let mut camera = RtsCamera { input_deadzone: 0.2, pan_input, zoom_input: Some(-0.15), ..Default::default() };
+
+        for _ in 0..3 {
+            let scroll = Some(Vec2::new(0.0, 0.1));
+            camera.tick(scroll, None, VIEWPORT, &keys(&[]), &settings.camera(), None, DELTA, 1.0);
+        }
+
+        assert_eq!((camera.pan_velocity, camera.zoom_velocity), (Vec2::zero(), 0.0));
+        assert_eq!(camera.last_scroll_sec, f64::NEG_INFINITY);
+        assert_close(camera.idle_secs(), 3.0 * DELTA);
+
+        // Input past the deadzone still counts
+        let scroll = Some(Vec2::new(0.0, 0.5));
+        camera.tick(scroll, None, VIEWPORT, &keys(&[]), &settings.camera(), None, DELTA, 1.0);
+        assert!(camera.zoom_velocity != 0.0);
+        assert_eq!(camera.idle_secs(), 0.0);
+    }
+
+    #[test]
+    fn actions_with_modifiers_need_them_held() {
+        let mut settings = TestSettings::default();
+        settings.turn.modifier_keys = Cow::Borrowed(&[KeyCode::LControl]);
+        let intent = |keyboard: &Input<KeyCode>| {
+            let mut camera = RtsCamera::default();
+            camera.read_intent(None, None, VIEWPORT, keyboard, &settings.camera(), DELTA, 0.0)
+        };
+
+        let without = intent(&keys(&[KeyCode::Q, KeyCode::Right]));
+        assert_eq!(without.turn_accel, 0.0);
+        assert!(without.pan_accel.x > 0.0);
+
+        let with = intent(&keys(&[KeyCode::Q, KeyCode::Right, KeyCode::LControl]));
+        assert_close(with.turn_accel, settings.turn.keyboard_accel * DELTA);
+        assert_eq!(with.pan_accel, without.pan_accel);
+    }
+
+    #[test]
+    fn cursor_projects_onto_a_raised_plane() {
+        let settings = TestSettings::default();
+        let mut camera = RtsCamera::default();
+        tick(&mut camera, &settings, &keys(&[]));
+        let (window, camera_component) = (window(None), perspective_camera());
+        let cursor = Vec2::new(300.0, 200.0);
+
+        let raised = camera.cursor_to_plane(cursor, &window, &camera_component, 5.0).unwrap();
+        let ground = camera.cursor_to_ground(cursor, &window, &camera_component).unwrap();
+        assert_close(raised.y, 5.0);
+        assert_eq!(ground.y, 0.0);
+
+        // The raised plane is hit first, along the same ray
+        let eye = camera.camera_translation();
+        assert!((raised - eye).length() < (ground - eye).length());
+        assert!((raised - eye).normalize().abs_diff_eq((ground - eye).normalize(), 1e-4));
+
+        // A ground plane at the same height gives the same point
+        camera.ground = Plane::horizontal(5.0);
+        assert!(camera.cursor_to_ground(cursor, &window, &camera_component).unwrap().abs_diff_eq(raised, 1e-4));
+    }
+
+    #[test]
+    fn turn_input_smoothing_softens_flicker_in_the_margin() {
+        let largest_change = |smoothing: f32| {
+            let mut settings = TestSettings::default();
+            settings.turn.input_smoothing = smoothing;
+            let mut camera = RtsCamera::default();
+            let (mut last, mut largest) = (0.0, 0.0f32);
+
+            // The cursor flickers in and out of the turn margin at the left edge every other frame
+            for frame in 0..60 {
+                let cursor = if frame % 4 < 2 { Vec2::new(1.0, 600.0) } else { VIEWPORT / 2.0 };
+                let intent =
+                    camera.read_intent(None, Some(cursor), VIEWPORT, &keys(&[]), &settings.camera(), DELTA, 0.0);
+                largest = largest.max((intent.turn_accel - last).abs());
+                last = intent.turn_accel;
+            }
+
+            largest
+        };
+
+        let (unsmoothed, smoothed) = (largest_change(0.0), largest_change(0.2));
+        assert!(unsmoothed > 0.0 && smoothed > 0.0);
+        assert!(smoothed < unsmoothed / 2.0, "{} is not much smoother than {}", smoothed, unsmoothed);
+    }
+
+    #[test]
+    fn frustum_corners_surround_the_view() {
+        let settings = TestSettings::default();
+        let mut camera = RtsCamera::default();
+        tick(&mut camera, &settings, &keys(&[]));
+        let corners = camera.frustum_corners(&perspective_camera());
+        let (eye, rotation) = (camera.camera_translation(), camera.rotation);
+        let local = |corner: Vec3| rotation.conjugate() * (corner - eye);
+
+        for (near, far) in corners[..4].iter().zip(&corners[4..]) {
+            // Each far corner is along the same ray from the camera as the near corner
+            assert!((*near - eye).length() < (*far - eye).length());
+            assert!((*near - eye).normalize().abs_diff_eq((*far - eye).normalize(), 1e-3));
+            assert!(local(*near).z < 0.0);
+        }
+
+        // Bottom left, bottom right, top right and top left as seen from the camera
+        let near: Vec<_> = corners[..4].iter().map(|corner| local(*corner)).collect();
+        assert!(near[0].x < 0.0 && near[0].y < 0.0);
+        assert!(near[1].x > 0.0 && near[1].y < 0.0);
+        assert!(near[2].x > 0.0 && near[2].y > 0.0);
+        assert!(near[3].x < 0.0 && near[3].y > 0.0);
+    }
+
+    #[test]
+    fn scroll_can_be_suppressed_in_the_turn_margin() {
+        let mut settings = TestSettings::default();
+        let zoom_accel = |settings: &TestSettings, cursor: Vec2| {
+            let mut camera = RtsCamera::default();
+            let scroll = Some(Vec2::new(0.0, 1.0));
+            camera.read_intent(scroll, Some(cursor), VIEWPORT, &keys(&[]), &settings.camera(), DELTA, 0.0).zoom_accel
+        };
+        let (turning, centre) = (Vec2::new(1.0, 600.0), VIEWPORT / 2.0);
+
+        assert!(zoom_accel(&settings, turning) < 0.0);
+
+        settings.zoom.suppress_scroll_in_turn_margin = true;
+        assert_eq!(zoom_accel(&settings, turning), 0.0);
+        assert!(zoom_accel(&settings, centre) < 0.0);
+    }
+
+    #[test]
+    fn pitch_smoothing_eases_after_a_zoom_snap() {
+        let pitch_after_snap = |smoothing: f32, frames: u32| {
+            let mut settings = TestSettings::default();
+            settings.zoom.pitch_smoothing = smoothing;
+            let mut camera = RtsCamera { zoom_distance: 10.0, ..Default::default() };
+            tick(&mut camera, &settings, &keys(&[]));
+            camera.zoom_distance = 80.0;
+
+            for _ in 0..frames {
+                tick(&mut camera, &settings, &keys(&[]));
+            }
+
+            camera.zoom_pitch.unwrap()
+        };
+
+        let zoom = ZoomSettings::default();
+        let (from, to) = (zoom.pitch_at(10.0), zoom.pitch_at(80.0));
+        assert_eq!(pitch_after_snap(0.0, 1), to);
+
+        let eased = pitch_after_snap(0.2, 1);
+        assert!(eased > from.min(to) && eased < from.max(to));
+        assert_close((eased - from) / (to - from), 1.0 - (-DELTA / 0.2).exp());
+        assert_close(pitch_after_snap(0.2, 180), to);
+    }
+
+    #[test]
+    fn camera_keeps_a_minimum_distance_from_the_target() {
+        let mut settings = TestSettings::default();
+        settings.zoom.distance_range = 0.0..=100.0;
+        settings.zoom.idle_deceleration = 0.0;
+        let mut camera = RtsCamera { zoom_distance: 1.0, zoom_input: Some(-1.0), ..Default::default() };
+
+        for _ in 0..120 {
+            tick(&mut camera, &settings, &keys(&[]));
+        }
+
+        assert_eq!(camera.zoom_distance, 0.0);
+        assert_eq!(camera.camera_distance, MIN_CAMERA_DISTANCE);
+
+        let transform = camera.camera_transform();
+        assert!(transform.translation.is_finite() && transform.rotation.is_finite());
+        assert!((transform.translation - camera.looking_at).length() > 0.0);
+        assert!(transform.compute_matrix().inverse().is_finite());
+    }
+
+    #[test]
+    fn drag_box_projects_to_a_ground_quad() {
+        let settings = TestSettings::default();
+        let mut camera = RtsCamera::default();
+        tick(&mut camera, &settings, &keys(&[]));
+        let (window, camera_component) = (window(None), perspective_camera());
+
+        let (min, max) = (Vec2::new(440.0, 160.0), Vec2::new(840.0, 560.0));
+        let quad = camera.screen_rect_to_ground(min, max, &window, &camera_component).unwrap();
+        assert!(quad.iter().all(|corner| corner.y.abs() < 1e-4));
+
+        // Facing down -Z, the bottom of the box is nearer the camera and its left is towards -X
+        let [bottom_left, bottom_right, top_right, top_left] = quad;
+        assert!(bottom_left.x < 0.0 && bottom_right.x > 0.0);
+        assert!(top_left.z < bottom_left.z && top_right.z < bottom_right.z);
+
+        // Perspective makes the far edge wider, and the target is within the quad
+        assert!(top_right.x - top_left.x > bottom_right.x - bottom_left.x);
+        assert!(bottom_left.z > 0.0 && top_left.z < 0.0);
+
+        // Looking level, the top of the screen is above the horizon
+        camera.rotation = Quat::identity();
+        assert_eq!(camera.screen_rect_to_ground(min, max, &window, &camera_component), None);
+    }
+
+    #[test]
+    fn zooming_in_and_out_coast_at_their_own_rates() {
+        let mut settings = TestSettings::default();
+        settings.zoom.idle_deceleration_out = Some(2.0);
+        settings.zoom.idle_deceleration_in = Some(8.0);
+        let coast = |settings: &TestSettings, zoom_velocity: f32| {
+            let mut camera = RtsCamera { zoom_distance: 50.0, zoom_velocity, ..Default::default() };
+            tick(&mut camera, settings, &keys(&[]));
+            camera.zoom_velocity
+        };
+
+        assert_close(coast(&settings, 3.0), 3.0 - 2.0 * DELTA);
+        assert_close(coast(&settings, -3.0), -3.0 + 8.0 * DELTA);
+
+        // Either defaults to the shared idle deceleration
+        settings.zoom.idle_deceleration_in = None;
+        assert_close(coast(&settings, -3.0), -3.0 + settings.zoom.idle_deceleration * DELTA);
+    }
+
+    #[test]
+    fn framing_a_box_fits_it_in_view() {
+        let settings = TestSettings::default();
+        let (window, camera_component) = (window(None), perspective_camera());
+        let (min, max) = (Vec3::new(10.0, 0.0, -30.0), Vec3::new(30.0, 8.0, -10.0));
+
+        let mut camera = RtsCamera::default();
+        camera.frame_aabb(min, max, &camera_component, &settings.zoom);
+        let target = camera.tween.unwrap().to;
+        assert_eq!(target.looking_at, Vec3::new(20.0, 0.0, -20.0));
+        assert!(settings.zoom.distance_range.contains(&target.zoom_distance));
+
+        while camera.tween.is_some() {
+            tick(&mut camera, &settings, &keys(&[]));
+        }
+
+        for &x in &[min.x, max.x] {
+            for &y in &[min.y, max.y] {
+                for &z in &[min.z, max.z] {
+                    assert!(camera.is_on_screen(Vec3::new(x, y, z), &window, &camera_component));
+                }
+            }
+        }
+
+        // The distance is clamped to the zoom range for a box too large to fit
+        camera.frame_aabb(min * 100.0, max * 100.0, &camera_component, &settings.zoom);
+        assert_eq!(camera.tween.unwrap().to.zoom_distance, *settings.zoom.distance_range.end());
+    }
+
+    #[test]
+    fn render_smoothing_trails_the_logical_target() {
+        let mut app = TestApp::new();
+        let entity = app.world.spawn((RtsCamera { render_smoothing: 0.2, ..Default::default() }, Transform::default()));
+        let trail = |app: &TestApp| {
+            let camera = app.world.get::<RtsCamera>(entity).unwrap();
+            let rendered = app.world.get::<Transform>(entity).unwrap().translation;
+            rendered - camera.transform_looking_at(camera.looking_at).translation
+        };
+
+        app.resources.get_mut::<Input<KeyCode>>().unwrap().press(KeyCode::Right);
+        for _ in 0..30 {
+            app.update();
+        }
+
+        let camera = app.world.get::<RtsCamera>(entity).unwrap();
+        assert!(camera.looking_at.x > 0.0);
+        assert!(trail(&app).x < 0.0);
+        assert!(trail(&app).abs_diff_eq(camera.render_looking_at.unwrap() - camera.looking_at, 1e-4));
+
+        app.resources.get_mut::<Input<KeyCode>>().unwrap().release(KeyCode::Right);
+        for _ in 0..300 {
+            app.update();
+        }
+
+        assert!(trail(&app).length() < 1e-3);
+    }
+
+    #[test]
+    fn opposing_pan_keys_follow_the_policy() {
+        let intent_with = |policy: OpposingKeys, pan_input: Option<PanMagnitudes>| {
+            let mut settings = TestSettings::default();
+            settings.pan.opposing_keys = policy;
+            let mut camera = RtsCamera { pan_input, ..Default::default() };
+            let keyboard = keys(&[KeyCode::Left, KeyCode::Right]);
+            camera.read_intent(None, None, VIEWPORT, &keyboard, &settings.camera(), DELTA, 0.0)
+        };
+        let accel = PanSettings::default().keyboard_accel * DELTA;
+
+        // Both keys held cancel out, and the camera decelerates as if there were no input
+        for &policy in &[OpposingKeys::Cancel, OpposingKeys::Ignore] {
+            let intent = intent_with(policy, None);
+            assert_eq!(intent.pan_accel.x, 0.0);
+            assert_eq!(intent.pan_x_decel, Deceleration::default());
+        }
+
+        // Unequal analog input cancels to the net input, or is ignored
+        let uneven = Some(PanMagnitudes { left: 0.5, right: 1.0, ..Default::default() });
+        let cancelled = intent_with(OpposingKeys::Cancel, uneven);
+        assert_close(cancelled.pan_accel.x, accel / 2.0);
+        assert_eq!(cancelled.pan_x_decel, Deceleration { pos: false, neg: true });
+
+        let ignored = intent_with(OpposingKeys::Ignore, uneven);
+        assert_eq!(ignored.pan_accel.x, 0.0);
+        assert_eq!(ignored.pan_x_decel, Deceleration::default());
+    }
+
+    #[test]
+    fn turning_toward_a_heading_takes_the_short_arc_and_stops() {
+        let mut settings = TestSettings::default();
+        let mut camera = RtsCamera { yaw: 0.2, ..Default::default() };
+        assert_close(camera.yaw_to(TAU - 0.3), -0.5);
+
+        camera.turn_toward(TAU - 0.3, &settings.turn);
+        while camera.tween.is_some() {
+            tick(&mut camera, &settings, &keys(&[]));
+            assert!(camera.yaw <= 0.2 + 1e-5 || camera.yaw >= TAU - 0.3 - 1e-5, "turned the long way");
+        }
+
+        assert_close(camera.yaw, TAU - 0.3);
+        tick(&mut camera, &settings, &keys(&[]));
+        assert_close(camera.yaw, TAU - 0.3);
+        assert_eq!(camera.turn_velocity, 0.0);
+
+        // When the short arc passes through yaw which is not allowed, the camera turns the long way
+        settings.turn.yaw_range = 0.1..=6.0;
+        let mut camera = RtsCamera { yaw: 0.5, ..Default::default() };
+        camera.turn_toward(5.5, &settings.turn);
+        assert_close(camera.tween.unwrap().yaw_delta, 5.0);
+    }
+
+    #[test]
+    fn state_plugin_only_runs_the_system_in_the_state() {
+        #[derive(Clone, Debug)]
+        enum GameState {
+            Playing,
+            Paused,
+        }
+
+        let mut builder = App::build();
+        builder
+            .add_resource(Time::default())
+            .add_resource(Windows::default())
+            .add_resource(FixedTimesteps::default())
+            .add_resource(Input::<KeyCode>::default())
+            .add_resource(Input::<MouseButton>::default())
+            .add_event::<MouseWheel>()
+            .add_event::<MouseMotion>()
+            .add_event::<WindowFocused>()
+            .add_resource(State::new(GameState::Paused))
+            .add_stage_after(stage::UPDATE, "game_state", StateStage::<GameState>::default())
+            .add_plugin(RtsCameraPlugin::run_in_state("game_state", GameState::Playing));
+        let mut app = builder.app;
+        let entity = app.world.spawn((RtsCamera::default(), Transform::default()));
+
+        for _ in 0..3 {
+            app.update();
+        }
+
+        assert_eq!(*app.world.get::<Transform>(entity).unwrap(), Transform::default());
+        assert!(app.world.get::<RtsCamera>(entity).unwrap().rendered_transform.is_none());
+
+        app.resources.get_mut::<State<GameState>>().unwrap().set_next(GameState::Playing).unwrap();
+        for _ in 0..2 {
+            app.update();
+        }
+
+        let camera = app.world.get::<RtsCamera>(entity).unwrap();
+        assert_eq!(Some(*app.world.get::<Transform>(entity).unwrap()), camera.rendered_transform);
+        assert!(camera.rendered_transform.unwrap() != Transform::default());
+    }
+
+    #[test]
+    fn relative_mouse_input_ignores_the_cursor() {
+        let run = |mouse_input: MouseInput| {
+            let mut app = TestApp::new();
+            // At the left edge, below the turn margin, where the cursor would pan
+            app.resources.get_mut::<Windows>().unwrap().add(window(Some(Vec2::new(1.0, VIEWPORT.y / 2.0))));
+            let entity = app.world.spawn((RtsCamera { mouse_input, ..Default::default() }, Transform::default()));
+            app.resources.get_mut::<Events<MouseMotion>>().unwrap().send(MouseMotion { delta: Vec2::new(20.0, 0.0) });
+            app.update();
+            let camera = app.world.get::<RtsCamera>(entity).unwrap();
+            (camera.yaw, camera.pan_velocity)
+        };
+
+        let motion_scale = TurnSettings::default().motion_scale;
+        let (yaw, pan_velocity) = run(MouseInput::Relative);
+        assert_close(yaw, TAU - 20.0 * motion_scale);
+        assert_eq!(pan_velocity, Vec2::zero());
+
+        let (yaw, pan_velocity) = run(MouseInput::Cursor);
+        assert_eq!(yaw, 0.0);
+        assert!(pan_velocity.x < 0.0);
+    }
+
+    #[test]
+    fn stopping_zoom_carries_momentum_into_the_pan() {
+        let mut settings = TestSettings::default();
+        settings.zoom.idle_deceleration = 0.0;
+        settings.pan.idle_deceleration = 0.0;
+        let release_zoom = |cross_coupling: CrossCoupling| {
+            let mut camera = RtsCamera { zoom_distance: 50.0, cross_coupling, ..Default::default() };
+            for _ in 0..10 {
+                tick(&mut camera, &settings, &keys(&[KeyCode::Equals]));
+            }
+
+            let zoom_velocity = camera.zoom_velocity;
+            assert_eq!(camera.pan_velocity, Vec2::zero());
+            tick(&mut camera, &settings, &keys(&[]));
+            (zoom_velocity, camera.pan_velocity)
+        };
+
+        // Zooming in nudges the camera forward
+        let (zoom_velocity, pan_velocity) = release_zoom(CrossCoupling { zoom_to_pan: 0.5, pan_to_zoom: 0.0 });
+        assert!(zoom_velocity < 0.0);
+        assert_eq!(pan_velocity.x, 0.0);
+        assert_close(pan_velocity.y, -zoom_velocity * 0.5);
+
+        let (_, pan_velocity) = release_zoom(CrossCoupling::default());
+        assert_eq!(pan_velocity, Vec2::zero());
+    }
+
+    #[test]
+    fn limit_accessors_reflect_the_camera_reaching_each_limit() {
+        let mut settings = TestSettings::default();
+        settings.zoom.idle_deceleration = 0.0;
+        settings.turn.yaw_range = 0.5..=1.0;
+        let mut camera = RtsCamera { yaw: 0.75, ..Default::default() };
+        let at_limits = |camera: &RtsCamera, settings: &TestSettings| {
+            let (zoom, turn) = (&settings.zoom, &settings.turn);
+            [camera.at_zoom_min(zoom), camera.at_zoom_max(zoom), camera.at_yaw_min(turn), camera.at_yaw_max(turn)]
+        };
+
+        tick(&mut camera, &settings, &keys(&[]));
+        assert_eq!(at_limits(&camera, &settings), [false; 4]);
+
+        let hold = |camera: &mut RtsCamera, key: KeyCode| {
+            for _ in 0..2000 {
+                tick(camera, &settings, &keys(&[key]));
+            }
+        };
+
+        hold(&mut camera, KeyCode::Minus);
+        assert_eq!(at_limits(&camera, &settings), [false, true, false, false]);
+        hold(&mut camera, KeyCode::Equals);
+        assert_eq!(at_limits(&camera, &settings), [true, false, false, false]);
+        hold(&mut camera, KeyCode::Q);
+        assert_eq!(at_limits(&camera, &settings), [true, false, false, true]);
+        hold(&mut camera, KeyCode::E);
+        assert_eq!(at_limits(&camera, &settings), [true, false, true, false]);
+
+        // A yaw range covering the whole circle has no limits
+        settings.turn.yaw_range = 0.0..=TAU;
+        assert!(!camera.at_yaw_min(&settings.turn) && !camera.at_yaw_max(&settings.turn));
+    }
+
+    #[test]
+    fn time_scale_slows_the_camera_proportionally() {
+        let mut settings = TestSettings::default();
+        settings.pan.idle_deceleration = 0.0;
+        let camera = |time_scale: f32| {
+            let pan_velocity = Vec2::new(1.0, 0.0);
+            RtsCamera { pan_velocity, time_scale, ..Default::default() }
+        };
+        let (mut normal, mut slow) = (camera(1.0), camera(0.5));
+
+        for _ in 0..60 {
+            tick(&mut normal, &settings, &keys(&[]));
+            tick(&mut slow, &settings, &keys(&[]));
+        }
+
+        assert!(normal.looking_at.x > 0.5);
+        assert_close(slow.looking_at.x, normal.looking_at.x / 2.0);
+
+        // Smooth moves are slowed too
+        normal.focus_smooth(Vec3::new(10.0, 0.0, 0.0), ZoomBehavior::Keep);
+        slow.focus_smooth(Vec3::new(10.0, 0.0, 0.0), ZoomBehavior::Keep);
+        normal.pan_velocity = Vec2::zero();
+        slow.pan_velocity = Vec2::zero();
+        let frames = (normal.focus_secs / DELTA).ceil() as usize;
+
+        for _ in 0..frames {
+            tick(&mut normal, &settings, &keys(&[]));
+            tick(&mut slow, &settings, &keys(&[]));
+        }
+
+        assert!(normal.tween.is_none());
+        assert!(slow.tween.is_some());
+
+        for _ in 0..=frames {
+            tick(&mut slow, &settings, &keys(&[]));
+        }
+
+        assert!(slow.tween.is_none());
+    }
+
+    #[test]
+    fn max_pitch_rate_caps_the_change_in_pitch() {
+        let mut settings = TestSettings::default();
+        settings.zoom.max_pitch_rate = Some(0.6);
+        let (far, near) = (settings.zoom.pitch_at(100.0), settings.zoom.pitch_at(5.0));
+        assert!(far != near);
+
+        let mut camera = RtsCamera { zoom_distance: 100.0, ..Default::default() };
+        tick(&mut camera, &settings, &keys(&[]));
+        assert_eq!(camera.zoom_pitch, Some(far));
+
+        // Snapping across the angle change zone slews the pitch at the maximum rate
+        camera.zoom_distance = 5.0;
+        let mut last = far;
+        for _ in 0..10 {
+            tick(&mut camera, &settings, &keys(&[]));
+            let pitch = camera.zoom_pitch.unwrap();
+            assert_close((pitch - last).abs(), 0.6 * DELTA);
+            last = pitch;
+        }
+
+        for _ in 0..600 {
+            tick(&mut camera, &settings, &keys(&[]));
+        }
+
+        assert_close(camera.zoom_pitch.unwrap(), near);
+
+        settings.zoom.max_pitch_rate = None;
+        camera.zoom_distance = 100.0;
+        tick(&mut camera, &settings, &keys(&[]));
+        assert_eq!(camera.zoom_pitch, Some(far));
+    }
+
+    #[test]
+    fn looking_at_an_entity_once_moves_there_and_releases_control() {
+        let mut app = TestApp::new();
+        let position = Vec3::new(5.0, 0.0, -5.0);
+        let target = app.world.spawn((GlobalTransform::from_translation(position),));
+        let entity = app.world.spawn((RtsCamera::default(), Transform::default()));
+        let camera = |app: &TestApp| {
+            let camera = app.world.get::<RtsCamera>(entity).unwrap();
+            (camera.looking_at, camera.tween, camera.focus_entity)
+        };
+
+        app.world.get_mut::<RtsCamera>(entity).unwrap().look_at_entity_once(target);
+        app.update();
+        let (_, tween, focus_entity) = camera(&app);
+        assert_eq!(focus_entity, None);
+        assert_eq!(tween.unwrap().to.looking_at, position);
+
+        for _ in 0..40 {
+            app.update();
+        }
+
+        let (looking_at, tween, _) = camera(&app);
+        assert!(tween.is_none());
+        assert!(looking_at.abs_diff_eq(position, 1e-4));
+
+        // The target is not followed, and the player can pan away again
+        *app.world.get_mut::<GlobalTransform>(target).unwrap() = GlobalTransform::from_translation(Vec3::zero());
+        app.resources.get_mut::<Input<KeyCode>>().unwrap().press(KeyCode::Up);
+        app.update();
+        assert!(camera(&app).0.z < position.z);
+
+        // Looking at a missing entity does nothing
+        app.resources.get_mut::<Input<KeyCode>>().unwrap().release(KeyCode::Up);
+        app.world.get_mut::<RtsCamera>(entity).unwrap().pan_velocity = Vec2::zero();
+        app.world.despawn(target).unwrap();
+        app.world.get_mut::<RtsCamera>(entity).unwrap().look_at_entity_once(target);
+        let (before, _, _) = camera(&app);
+        app.update();
+        let (looking_at, tween, focus_entity) = camera(&app);
+        assert_eq!(focus_entity, None);
+        assert!(tween.is_none());
+        assert_eq!(looking_at, before);
+    }
+
+    #[test]
+    fn scroll_accumulation_sums_or_takes_the_latest_event() {
+        let zoom_velocity_after = |scroll_accumulation: ScrollAccumulation| {
+            let mut app = TestApp::new();
+            let mut zoom = ZoomSettings { scroll_accumulation, ..Default::default() };
+            zoom.idle_deceleration = 0.0;
+            let camera = RtsCamera { zoom_distance: 50.0, ..Default::default() };
+            let entity = app.world.spawn((camera, Transform::default(), zoom));
+
+            let mut events = app.resources.get_mut::<Events<MouseWheel>>().unwrap();
+            for &y in &[0.1, 0.2, 0.3] {
+                events.send(MouseWheel { unit: MouseScrollUnit::Line, x: 0.0, y });
+            }
+
+            drop(events);
+            app.update();
+            app.world.get::<RtsCamera>(entity).unwrap().zoom_velocity
+        };
+
+        let scroll_accel = ZoomSettings::default().scroll_accel;
+        assert_close(zoom_velocity_after(ScrollAccumulation::Latest), -0.3 * scroll_accel);
+        assert_close(zoom_velocity_after(ScrollAccumulation::Sum), -0.6 * scroll_accel);
+    }
+
+    #[test]
+    fn response_curves_shape_the_acceleration_per_source() {
+        let mut settings = TestSettings::default();
+        let margin = settings.pan.mouse_accel_margin;
+        let edge_accel = |settings: &TestSettings, depth: f32| {
+            let mut camera = RtsCamera::default();
+            let cursor = Some(Vec2::new(margin * (1.0 - depth), VIEWPORT.y / 2.0));
+            let intent = camera.read_intent(None, cursor, VIEWPORT, &keys(&[]), &settings.camera(), DELTA, 0.0);
+            -intent.pan_accel.x
+        };
+        let key_accel = |settings: &TestSettings, magnitude: f32| {
+            let pan_input = Some(PanMagnitudes { right: magnitude, ..Default::default() });
+            let mut camera = RtsCamera { pan_input, ..Default::default() };
+            let intent = camera.read_intent(None, None, VIEWPORT, &keys(&[]), &settings.camera(), DELTA, 0.0);
+            intent.pan_accel.x
+        };
+
+        let full_edge = settings.pan.mouse_accel * DELTA;
+        let full_key = settings.pan.keyboard_accel * DELTA;
+
+        // By default, the edge gives the full acceleration anywhere in the margin
+        assert_close(edge_accel(&settings, 0.25), full_edge);
+        assert_close(edge_accel(&settings, 1.0), full_edge);
+        assert_close(key_accel(&settings, 0.5), full_key * 0.5);
+
+        // Each source has its own curve
+        settings.pan.edge_response = ResponseCurve::Quadratic;
+        assert_close(edge_accel(&settings, 0.25), full_edge / 16.0);
+        assert_close(edge_accel(&settings, 1.0), full_edge);
+        assert_close(key_accel(&settings, 0.5), full_key * 0.5);
+
+        settings.pan.keyboard_response = ResponseCurve::Quadratic;
+        assert_close(key_accel(&settings, 0.5), full_key * 0.25);
+        assert_close(key_accel(&settings, 1.0), full_key);
+    }
+
+    #[test]
+    fn circle_bounds_project_the_target_back_onto_the_circle() {
+        let bounds = PanBounds::circle(Vec2::new(1.0, 0.0), 5.0);
+
+        // Panning right at yaw zero moves out along +X, while panning forward moves along the edge
+        let mut camera =
+            RtsCamera { looking_at: Vec3::new(9.0, 0.0, 0.0), pan_velocity: Vec2::new(3.0, 1.0), ..Default::default() };
+        camera.apply_bounds(&bounds, DELTA);
+        assert_eq!(camera.looking_at, Vec3::new(6.0, 0.0, 0.0));
+        assert!(camera.pan_velocity.abs_diff_eq(Vec2::new(0.0, 1.0), 1e-5));
+
+        // Within the circle, the target is left alone
+        let looking_at = Vec3::new(3.0, 0.0, -3.0);
+        let mut camera = RtsCamera { looking_at, pan_velocity: Vec2::new(3.0, 1.0), ..Default::default() };
+        camera.apply_bounds(&bounds, DELTA);
+        assert_eq!(camera.looking_at, looking_at);
+        assert_eq!(camera.pan_velocity, Vec2::new(3.0, 1.0));
+
+        let diagonal = BoundsShape::Circle { center: Vec2::zero(), radius: 5.0 }.constrain(Vec2::new(6.0, 8.0));
+        assert!(diagonal.abs_diff_eq(Vec2::new(3.0, 4.0), 1e-5));
+    }
+
+    #[test]
+    fn effective_limits_reflect_the_override_and_time_scale() {
+        let settings = TestSettings::default();
+        let mut camera = RtsCamera { zoom_distance: 40.0, ..Default::default() };
+        let pan_factor = settings.pan.speed_zoom_factor(40.0, &settings.zoom);
+
+        let limits = camera.effective_limits(&settings.zoom, &settings.pan, &settings.turn);
+        assert_close(limits.max_pan_speed, settings.pan.max_speed * pan_factor);
+        assert_eq!(limits.max_zoom_velocity, settings.zoom.max_velocity);
+        assert_eq!(limits.max_turn_speed, settings.turn.max_speed);
+
+        // A boost through a settings override, on top of a fast-forwarded camera
+        let pan = PanSettings { max_speed: settings.pan.max_speed * 3.0, ..settings.pan.clone() };
+        camera.push_settings_override(SettingsOverride { pan: Some(pan), ..Default::default() });
+        camera.time_scale = 2.0;
+
+        let limits = camera.effective_limits(&settings.zoom, &settings.pan, &settings.turn);
+        assert_close(limits.max_pan_speed, settings.pan.max_speed * pan_factor * 6.0);
+        assert_eq!(limits.max_zoom_velocity, settings.zoom.max_velocity * 2.0);
+        assert_eq!(limits.max_turn_speed, settings.turn.max_speed * 2.0);
+    }
+
+    #[test]
+    fn points_are_classified_as_on_or_off_screen() {
+        let settings = TestSettings::default();
+        let (window, camera_component) = (window(None), perspective_camera());
+        let mut camera = RtsCamera::default();
+        tick(&mut camera, &settings, &keys(&[]));
+        let eye = camera.transform_looking_at(camera.looking_at).translation;
+        let on_screen = |point: Vec3| camera.is_on_screen(point, &window, &camera_component);
+
+        let centre = camera.world_to_screen(camera.looking_at, &window, &camera_component).unwrap();
+        assert!(centre.abs_diff_eq(VIEWPORT / 2.0, 1e-2));
+        assert!(on_screen(camera.looking_at));
+        assert!(on_screen(Vec3::new(1.0, 0.0, 1.0)));
+
+        // Outside of the frustum to the right, and behind the camera
+        let right = Vec3::new(1000.0, 0.0, 0.0);
+        let behind = eye + (eye - camera.looking_at) + Vec3::new(5.0, 0.0, 0.0);
+        assert!(!on_screen(right));
+        assert!(!on_screen(behind));
+
+        // Off screen points are moved onto the nearest edge in their direction
+        let edge = camera.screen_edge_position(right, &window, &camera_component).unwrap();
+        assert_close(edge.x, VIEWPORT.x);
+        assert!(edge.y > 0.0 && edge.y < VIEWPORT.y);
+        let edge = camera.screen_edge_position(behind, &window, &camera_component).unwrap();
+        let from_centre = (edge - VIEWPORT / 2.0).abs() / (VIEWPORT / 2.0);
+        assert_close(from_centre.max_element(), 1.0);
+        assert_eq!(camera.screen_edge_position(camera.looking_at, &window, &camera_component), Some(centre));
+    }
+
+    #[test]
+    fn panning_past_the_bounds_keeps_the_target_within_the_inset() {
+        let settings = TestSettings::default();
+        let (window, camera_component) = (window(None), perspective_camera());
+        let bounds = PanBounds::new(Vec2::new(-10.0, -10.0), Vec2::new(10.0, 10.0));
+        let mut camera = RtsCamera { focus_screen_margin: 100.0, ..Default::default() };
+        tick(&mut camera, &settings, &keys(&[]));
+
+        let desired = Vec3::new(30.0, 0.0, 0.0);
+        camera.looking_at = desired;
+        camera.apply_bounds(&bounds, DELTA);
+        assert_eq!(camera.looking_at, Vec3::new(10.0, 0.0, 0.0));
+        let clamped = camera.world_to_screen(desired, &window, &camera_component).unwrap();
+        assert!(clamped.x > VIEWPORT.x - 100.0);
+
+        // The target is moved back past the bounds just far enough to bring it into the inset
+        camera.keep_in_margin(desired, &window, &camera_component);
+        assert!(camera.looking_at.x > 10.0 && camera.looking_at.x < desired.x);
+        let position = camera.world_to_screen(desired, &window, &camera_component).unwrap();
+        assert!((position.x - (VIEWPORT.x - 100.0)).abs() < 1.0, "target at {}", position);
+        assert_close(position.y, VIEWPORT.y / 2.0);
+
+        // Within the bounds, nothing is moved
+        let mut camera = RtsCamera { focus_screen_margin: 100.0, ..Default::default() };
+        camera.looking_at = Vec3::new(5.0, 0.0, 0.0);
+        camera.keep_in_margin(camera.looking_at, &window, &camera_component);
+        assert_eq!(camera.looking_at, Vec3::new(5.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn focus_height_offset_raises_only_the_rendered_focus() {
+        let looking_at = Vec3::new(2.0, 0.0, -3.0);
+        let render = |focus_height_offset: f32| {
+            let mut app = TestApp::new();
+            let camera = RtsCamera { looking_at, focus_height_offset, ..Default::default() };
+            let entity = app.world.spawn((camera, Transform::default()));
+            app.update();
+
+            let camera = app.world.get::<RtsCamera>(entity).unwrap();
+            assert_eq!(camera.looking_at, looking_at);
+            (*app.world.get::<Transform>(entity).unwrap(), camera.camera_distance)
+        };
+
+        let (level, _) = render(0.0);
+        let (raised, distance) = render(1.5);
+        assert_eq!(raised.rotation, level.rotation);
+        assert!(raised.translation.abs_diff_eq(level.translation + Vec3::new(0.0, 1.5, 0.0), 1e-5));
+
+        let aim = raised.translation + raised.rotation * -Vec3::unit_z() * distance;
+        assert!(aim.abs_diff_eq(looking_at + Vec3::unit_y() * 1.5, 1e-4));
+    }
+
+    #[test]
+    fn unfocused_windows_freeze_the_camera_exactly() {
+        let run = |freeze_on_unfocus: bool| {
+            let mut app = TestApp::new();
+            app.resources.get_mut::<Windows>().unwrap().add(window(None));
+            let (pan_velocity, turn_velocity) = (Vec2::new(3.0, 0.0), 1.0);
+            let mut camera = RtsCamera { pan_velocity, turn_velocity, freeze_on_unfocus, ..Default::default() };
+            let turn = SettingsOverride { turn: Some(TurnSettings::default()), ..Default::default() };
+            camera.push_settings_override(turn);
+            let entity = app.world.spawn((camera, Transform::default()));
+            let state = |app: &TestApp| {
+                let camera = app.world.get::<RtsCamera>(entity).unwrap();
+                let transform = *app.world.get::<Transform>(entity).unwrap();
+                (camera.snapshot(), camera.idle_secs, transform, camera.settings_overrides.len())
+            };
+            let focus = |app: &mut TestApp, focused: bool| {
+                let mut events = app.resources.get_mut::<Events<WindowFocused>>().unwrap();
+                events.send(WindowFocused { id: WindowId::primary(), focused });
+            };
+
+            app.resources.get_mut::<Input<KeyCode>>().unwrap().press(KeyCode::Up);
+            app.update();
+            let before = state(&app);
+
+            focus(&mut app, false);
+            for _ in 0..10 {
+                app.update();
+            }
+
+            let unfocused = state(&app);
+            focus(&mut app, true);
+            app.update();
+            (before, unfocused, state(&app))
+        };
+
+        // The override stack survives being frozen
+        let (before, unfocused, refocused) = run(true);
+        assert_eq!(unfocused, before);
+        assert_eq!((unfocused.3, refocused.3), (1, 1));
+        assert!(refocused.0.looking_at != before.0.looking_at && refocused.0.yaw != before.0.yaw);
+
+        // By default, the camera carries on moving while unfocused
+        let (before, unfocused, _) = run(false);
+        assert!(unfocused.0.looking_at != before.0.looking_at);
+    }
+
+    #[test]
+    fn corner_policies_arbitrate_turning_and_panning() {
+        let mut settings = TestSettings::default();
+        let margin = settings.pan.mouse_accel_margin;
+        let (side, edge) = (margin - 2.0, margin - 6.0);
+        let corner = Some(Vec2::new(2.0, VIEWPORT.y - margin + edge));
+        let mut read = |policy: CornerPolicy| {
+            settings.turn.corner_policy = policy;
+            let mut camera = RtsCamera::default();
+            let intent = camera.read_intent(None, corner, VIEWPORT, &keys(&[]), &settings.camera(), DELTA, 0.0);
+            (intent.turn_accel, intent.pan_accel)
+        };
+
+        let (turn, pan) = read(CornerPolicy::Both);
+        assert!(turn > 0.0 && pan.y > 0.0);
+        assert_eq!(pan.x, 0.0);
+
+        assert_eq!(read(CornerPolicy::TurnPriority), (turn, Vec2::zero()));
+
+        let (no_turn, pan_only) = read(CornerPolicy::PanPriority);
+        assert_eq!(no_turn, 0.0);
+        assert!(pan_only.x < 0.0);
+        assert_eq!(pan_only.y, pan.y);
+
+        // Blending weighs each by how far the cursor is into its margin
+        let (blend_turn, blend_pan) = read(CornerPolicy::Blend);
+        assert_close(blend_turn, turn * side / (side + edge));
+        assert_close(blend_pan.y, pan.y * edge / (side + edge));
+        assert_eq!(blend_pan.x, 0.0);
+    }
+
+    #[test]
+    fn translation_and_rotation_are_smoothed_independently() {
+        let run = |render_pos_smoothing: f32, render_rot_smoothing: f32| {
+            let mut app = TestApp::new();
+            let camera = RtsCamera { render_pos_smoothing, render_rot_smoothing, ..Default::default() };
+            let entity = app.world.spawn((camera, Transform::default()));
+            app.update();
+            let start = *app.world.get::<Transform>(entity).unwrap();
+
+            app.world.get_mut::<RtsCamera>(entity).unwrap().looking_at = Vec3::new(10.0, 0.0, 0.0);
+            app.world.get_mut::<RtsCamera>(entity).unwrap().yaw = 1.0;
+            app.update();
+
+            let camera = app.world.get::<RtsCamera>(entity).unwrap();
+            let logical = camera.transform_looking_at(camera.looking_at);
+            assert_eq!(camera.looking_at, Vec3::new(10.0, 0.0, 0.0));
+            (start, *app.world.get::<Transform>(entity).unwrap(), logical)
+        };
+
+        // The translation trails, while the rotation is instant
+        let (start, rendered, logical) = run(0.5, 0.0);
+        assert_eq!(rendered.rotation, logical.rotation);
+        let progress = (rendered.translation - start.translation).length();
+        assert!(progress > 0.0 && progress < (logical.translation - start.translation).length() / 2.0);
+
+        // And the other way around
+        let (start, rendered, logical) = run(0.0, 0.5);
+        assert_eq!(rendered.translation, logical.translation);
+        assert!(!rendered.rotation.abs_diff_eq(logical.rotation, 1e-3));
+        let closeness = |rotation: Quat| rotation.dot(start.rotation).abs();
+        assert!(closeness(rendered.rotation) > closeness(logical.rotation));
+    }
+
+    #[test]
+    fn nudging_by_a_screen_width_moves_the_target_by_the_view_width() {
+        let settings = TestSettings::default();
+        let (window, camera_component) = (window(None), perspective_camera());
+        let mut camera = RtsCamera { yaw: 0.5, ..Default::default() };
+        tick(&mut camera, &settings, &keys(&[]));
+
+        let ground = |x: f32| camera.cursor_to_ground(Vec2::new(x, VIEWPORT.y / 2.0), &window, &camera_component);
+        let (left, right) = (ground(0.0).unwrap(), ground(VIEWPORT.x).unwrap());
+        let start = camera.looking_at;
+
+        assert!(camera.nudge_screen(Vec2::new(VIEWPORT.x, 0.0), &window, &camera_component));
+        let moved = camera.looking_at - start;
+        assert!(moved.abs_diff_eq(right - left, 1e-3), "moved by {} rather than {}", moved, right - left);
+        assert!(moved.y.abs() < 1e-5);
+
+        // Nudging to a point above the horizon does nothing
+        let looking_at = camera.looking_at;
+        assert!(!camera.nudge_screen(Vec2::new(0.0, 1e5), &window, &camera_component));
+        assert_eq!(camera.looking_at, looking_at);
+    }
+
+    #[test]
+    fn edge_pan_can_require_outward_motion() {
+        let mut settings = TestSettings::default();
+        let y = VIEWPORT.y / 2.0;
+        let pan_accel = |settings: &TestSettings, from: f32, to: f32| {
+            let mut camera = RtsCamera { last_cursor: Some(Vec2::new(from, y)), ..Default::default() };
+            let cursor = Some(Vec2::new(to, y));
+            let intent = camera.read_intent(None, cursor, VIEWPORT, &keys(&[]), &settings.camera(), DELTA, 0.0);
+            (intent.pan_accel.x, intent.pan_x_decel)
+        };
+
+        let (outward, undecelerated) = pan_accel(&settings, 10.0, 5.0);
+        assert!(outward < 0.0);
+        assert_eq!(pan_accel(&settings, 2.0, 5.0), (outward, undecelerated));
+        assert_eq!(pan_accel(&settings, 5.0, 5.0), (outward, undecelerated));
+
+        // Only moving towards the edge pans, and otherwise the camera decelerates as usual
+        settings.pan.edge_pan_requires_outward_motion = true;
+        assert_eq!(pan_accel(&settings, 10.0, 5.0), (outward, undecelerated));
+        assert_eq!(pan_accel(&settings, 2.0, 5.0), (0.0, Deceleration::default()));
+        assert_eq!(pan_accel(&settings, 5.0, 5.0), (0.0, Deceleration::default()));
+
+        // At the right edge, outward is to the right
+        let right = VIEWPORT.x - 5.0;
+        assert!(pan_accel(&settings, right - 5.0, right).0 > 0.0);
+        assert_eq!(pan_accel(&settings, right + 3.0, right).0, 0.0);
+    }
+
+    #[test]
+    fn transform_hook_post_processes_the_written_transform() {
+        let mut app = TestApp::new();
+        let camera = || RtsCamera { looking_at: Vec3::new(1.0, 0.0, 2.0), ..Default::default() };
+        let plain = app.world.spawn((camera(), Transform::default()));
+        let offset = Vec3::new(0.0, 3.0, 0.0);
+        let hook = TransformHook::new(move |camera, transform| {
+            assert_eq!(camera.looking_at, Vec3::new(1.0, 0.0, 2.0));
+            Transform { translation: transform.translation + offset, ..transform }
+        });
+        let hooked = app.world.spawn((camera(), Transform::default(), hook));
+        app.update();
+
+        let written = |entity| *app.world.get::<Transform>(entity).unwrap();
+        assert_eq!(written(hooked).translation, written(plain).translation + offset);
+        assert_eq!(written(hooked).rotation, written(plain).rotation);
+
+        // Picking uses the transform as written, while the camera state is unaffected
+        let camera = app.world.get::<RtsCamera>(hooked).unwrap();
+        assert_eq!(camera.rendered_transform, Some(written(hooked)));
+        assert_eq!(camera.looking_at, Vec3::new(1.0, 0.0, 2.0));
+    }
+
+    #[test]
+    fn focusing_can_set_the_zoom_distance_along_the_way() {
+        let settings = TestSettings::default();
+        let point = Vec3::new(8.0, 0.0, -6.0);
+        let mut camera = RtsCamera { zoom_distance: 20.0, ..Default::default() };
+        camera.focus_smooth(point, ZoomBehavior::SetTo(40.0));
+
+        // Halfway through, both the target and the zoom are on their way
+        let frames = (camera.focus_secs / DELTA).round() as usize;
+        for _ in 0..frames / 2 {
+            tick(&mut camera, &settings, &keys(&[]));
+        }
+
+        assert!(camera.looking_at.x > 0.0 && camera.looking_at.x < point.x);
+        assert!(camera.zoom_distance > 20.0 && camera.zoom_distance < 40.0);
+
+        for _ in 0..frames {
+            tick(&mut camera, &settings, &keys(&[]));
+        }
+
+        assert!(camera.tween.is_none());
+        assert!(camera.looking_at.abs_diff_eq(point, 1e-4));
+        assert_close(camera.zoom_distance, 40.0);
+
+        // Jumping keeps or resets the zoom at once
+        camera.focus_on(Vec3::zero(), ZoomBehavior::Keep);
+        assert_eq!((camera.looking_at, camera.zoom_distance), (Vec3::zero(), 40.0));
+        camera.focus_on(point, ZoomBehavior::Default);
+        assert_eq!((camera.looking_at, camera.zoom_distance), (point, camera.default_zoom_distance));
+    }
+
+    #[test]
+    fn popping_a_wider_zoom_range_eases_back_into_the_normal_range() {
+        let mut app = TestApp::new();
+        app.resources.get_mut::<RtsCameraDefaults>().unwrap().zoom.idle_deceleration = 0.0;
+        let normal = RtsCameraDefaults::default().zoom.distance_range;
+        let camera = RtsCamera { zoom_distance: 100.0, ..Default::default() };
+        let entity = app.world.spawn((camera, Transform::default()));
+        let zoom_distance = |app: &TestApp| app.world.get::<RtsCamera>(entity).unwrap().zoom_distance;
+
+        let overview = ZoomSettings { distance_range: 5.0..=300.0, idle_deceleration: 0.0, ..Default::default() };
+        let zoom_override = SettingsOverride { zoom: Some(overview), ..Default::default() };
+        app.world.get_mut::<RtsCamera>(entity).unwrap().push_settings_override(zoom_override);
+
+        // Zoom out past the normal limit while the override is active
+        app.resources.get_mut::<Input<KeyCode>>().unwrap().press(KeyCode::Minus);
+        for _ in 0..300 {
+            app.update();
+        }
+
+        app.resources.get_mut::<Input<KeyCode>>().unwrap().release(KeyCode::Minus);
+        let zoomed_out = zoom_distance(&app);
+        assert!(zoomed_out > *normal.end() + 10.0);
+
+        // Popping it returns into the normal range gradually rather than snapping
+        app.world.get_mut::<RtsCamera>(entity).unwrap().pop_settings_override();
+        let mut last = zoomed_out;
+        app.update();
+        assert!(zoom_distance(&app) < last && zoom_distance(&app) > *normal.end());
+
+        for _ in 0..300 {
+            last = zoom_distance(&app);
+            app.update();
+            assert!(zoom_distance(&app) <= last);
+        }
+
+        assert_eq!(zoom_distance(&app), *normal.end());
+    }
+
+    #[test]
+    fn zoom_decelerates_on_the_first_frame_without_scrolling() {
+        let settings = TestSettings::default();
+        let decel = settings.zoom.idle_deceleration * DELTA;
+        let mut camera = RtsCamera { zoom_distance: 50.0, zoom_velocity: 3.0, ..Default::default() };
+        assert_eq!(camera.last_scroll_sec, f64::NEG_INFINITY);
+
+        // The clock starts at zero, so a last scroll at zero would wrongly be within the grace period
+        camera.tick(None, None, VIEWPORT, &keys(&[]), &settings.camera(), None, DELTA, 0.0);
+        assert_close(camera.zoom_velocity, 3.0 - decel);
+
+        // Restoring a snapshot from before any scroll keeps the sentinel
+        let snapshot = RtsCamera::default().snapshot();
+        assert_eq!(snapshot.last_scroll_sec, None);
+        camera.restore(&snapshot);
+        assert_eq!(camera.last_scroll_sec, f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn scrolling_with_the_modifier_pans_instead_of_zooming() {
+        let mut settings = TestSettings::default();
+        settings.pan.scroll_pan_modifier = Some(KeyCode::LShift);
+        let scroll_accel = settings.pan.scroll_accel;
+        let read = |scroll: Vec2, keyboard: &Input<KeyCode>| {
+            let mut camera = RtsCamera::default();
+            let intent = camera.read_intent(Some(scroll), None, VIEWPORT, keyboard, &settings.camera(), DELTA, 1.0);
+            (intent.pan_accel, intent.zoom_accel, camera.last_scroll_pan_sec, camera.last_scroll_sec)
+        };
+
+        // Scrolling down pans right, as does scrolling a horizontal wheel right
+        let shift = keys(&[KeyCode::LShift]);
+        assert_eq!(read(Vec2::new(0.0, -1.0), &shift), (Vec2::new(scroll_accel, 0.0), 0.0, 1.0, f64::NEG_INFINITY));
+        assert_eq!(read(Vec2::new(0.0, 2.0), &shift).0, Vec2::new(-2.0 * scroll_accel, 0.0));
+        assert_eq!(read(Vec2::new(1.0, 0.0), &shift).0, Vec2::new(scroll_accel, 0.0));
+
+        // Without the modifier, the wheel zooms
+        let (pan_accel, zoom_accel, last_scroll_pan_sec, _) = read(Vec2::new(0.0, -1.0), &keys(&[]));
+        assert_eq!(pan_accel, Vec2::zero());
+        assert_eq!(zoom_accel, settings.zoom.scroll_accel);
+        assert_eq!(last_scroll_pan_sec, f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn released_turns_settle_on_the_nearest_increment() {
+        use std::f32::consts::FRAC_PI_4;
+
+        let turn_and_release = |snap_on_release: Option<f32>| {
+            let mut settings = TestSettings::default();
+            settings.turn.snap_on_release = snap_on_release;
+            let mut camera = RtsCamera::default();
+
+            for _ in 0..40 {
+                tick(&mut camera, &settings, &keys(&[KeyCode::Q]));
+            }
+
+            for _ in 0..300 {
+                tick(&mut camera, &settings, &keys(&[]));
+            }
+
+            camera
+        };
+
+        // Without an increment, the yaw stays where the turn coasted to
+        let free = turn_and_release(None).yaw;
+        let nearest = (free / FRAC_PI_4).round() * FRAC_PI_4;
+        assert!(shortest_yaw_delta(free, nearest).abs() > 0.05);
+
+        let snapped = turn_and_release(Some(FRAC_PI_4));
+        assert_close(snapped.yaw, nearest);
+        assert_eq!(snapped.turn_velocity, 0.0);
+        assert!(!snapped.turn_released);
+    }
+
+    #[test]
+    fn externally_controlled_cameras_track_their_state_without_drift() {
+        let mut app = TestApp::new();
+        let camera = RtsCamera { pan_velocity: Vec2::new(5.0, 0.0), turn_velocity: 1.0, ..Default::default() };
+        let entity = app.world.spawn((camera, Transform::default(), ExternalControl));
+        app.resources.get_mut::<Input<KeyCode>>().unwrap().press(KeyCode::Up);
+        let zoom = ZoomSettings::default();
+        let written = |app: &TestApp| *app.world.get::<Transform>(entity).unwrap();
+
+        for frame in 0..100 {
+            let state = CameraState {
+                looking_at: Vec3::new(frame as f32 * 0.5, 0.0, -(frame as f32)),
+                yaw: (frame as f32 * 0.1).rem_euclid(TAU),
+                zoom_distance: 10.0 + frame as f32 * 0.3,
+            };
+            let mut camera = app.world.get_mut::<RtsCamera>(entity).unwrap();
+            camera.looking_at = state.looking_at;
+            camera.yaw = state.yaw;
+            camera.zoom_distance = state.zoom_distance;
+            app.update();
+
+            // Input and velocities are ignored, so the camera is exactly where it was put
+            let camera = app.world.get::<RtsCamera>(entity).unwrap();
+            assert_eq!(camera.state(), state);
+            assert_eq!((camera.pan_velocity, camera.turn_velocity), (Vec2::zero(), 0.0));
+            let expected = state.transform(&zoom);
+            assert!(written(&app).translation.abs_diff_eq(expected.translation, 1e-4));
+            assert!(written(&app).rotation.abs_diff_eq(expected.rotation, 1e-5));
+        }
+
+        // Holding a state, the transform does not change at all
+        let held = written(&app);
+        for _ in 0..10 {
+            app.update();
+            assert_eq!(written(&app), held);
+        }
+    }
+
+    #[test]
+    fn turn_margin_never_covers_the_whole_window() {
+        let mut turn = TurnSettings { mouse_turn_margin: 0.9, ..Default::default() };
+        let short = Vec2::new(800.0, 100.0);
+        let at = |y: f32| Vec2::new(400.0, y);
+
+        // The margin is capped at half of the height, leaving the rest of the window to pan in
+        assert!(turn.in_mouse_turn_margin(at(60.0), short));
+        assert!(!turn.in_mouse_turn_margin(at(40.0), short));
+        assert!(!turn.in_mouse_turn_margin(at(0.0), short));
+
+        turn.mouse_turn_edge = VerticalEdge::Bottom;
+        assert!(turn.in_mouse_turn_margin(at(40.0), short));
+        assert!(!turn.in_mouse_turn_margin(at(60.0), short));
+
+        // At the left edge below the capped margin, the cursor pans rather than turns
+        let mut settings = TestSettings::default();
+        settings.turn.mouse_turn_margin = 0.9;
+        let mut camera = RtsCamera::default();
+        let cursor = Some(Vec2::new(1.0, 20.0));
+        let intent = camera.read_intent(None, cursor, short, &keys(&[]), &settings.camera(), DELTA, 0.0);
+        assert_eq!(intent.turn_accel, 0.0);
+        assert!(intent.pan_accel.x < 0.0);
+    }
+
+    #[test]
+    fn scroll_is_ignored_while_the_suppress_key_is_held() {
+        let mut settings = TestSettings::default();
+        settings.zoom.suppress_scroll_key = Some(KeyCode::LControl);
+        settings.zoom.idle_deceleration = 0.0;
+        let scroll = Some(Vec2::new(0.0, -1.0));
+        let held = keys(&[KeyCode::LControl]);
+
+        let mut camera = RtsCamera { zoom_distance: 50.0, ..Default::default() };
+        camera.tick(scroll, None, VIEWPORT, &held, &settings.camera(), None, DELTA, 1.0);
+        assert_eq!((camera.zoom_velocity, camera.zoom_distance), (0.0, 50.0));
+        assert_eq!(camera.last_scroll_sec, f64::NEG_INFINITY);
+
+        // The scroll is dropped rather than deferred, and once the key is released the wheel zooms
+        tick(&mut camera, &settings, &keys(&[]));
+        assert_eq!(camera.zoom_velocity, 0.0);
+        camera.tick(scroll, None, VIEWPORT, &keys(&[]), &settings.camera(), None, DELTA, 1.0);
+        assert_eq!(camera.zoom_velocity, settings.zoom.scroll_accel);
+
+        // The keys still zoom while it is held
+        let mut camera = RtsCamera { zoom_distance: 50.0, ..Default::default() };
+        tick(&mut camera, &settings, &keys(&[KeyCode::LControl, KeyCode::Minus]));
+        assert!(camera.zoom_velocity > 0.0);
+    }
+
+    #[test]
+    fn facing_a_point_turns_toward_it_without_panning() {
+        let mut settings = TestSettings::default();
+        let looking_at = Vec3::new(1.0, 0.0, 1.0);
+        let mut camera = RtsCamera { looking_at, yaw: 0.5, ..Default::default() };
+        let point = Vec3::new(6.0, 3.0, 1.0);
+
+        camera.face_point(point, &settings.turn);
+        assert!(camera.tween.unwrap().yaw_delta < 0.0);
+        while camera.tween.is_some() {
+            tick(&mut camera, &settings, &keys(&[]));
+        }
+
+        // The camera now faces along +X, towards the point
+        let forward = camera.rotation * -Vec3::unit_z();
+        let toward = (point - looking_at) * Vec3::new(1.0, 0.0, 1.0);
+        assert!(Vec3::new(forward.x, 0.0, forward.z).normalize().abs_diff_eq(toward.normalize(), 1e-4));
+        assert!(camera.looking_at.abs_diff_eq(looking_at, 1e-5));
+
+        // The yaw range is respected, and points straight above do nothing
+        settings.turn.yaw_range = 0.0..=1.0;
+        let mut camera = RtsCamera { looking_at, yaw: 0.5, ..Default::default() };
+        camera.face_point(looking_at + Vec3::new(-2.0f32.sin(), 0.0, -2.0f32.cos()), &settings.turn);
+        assert_eq!(camera.tween.unwrap().to.yaw, 1.0);
+
+        let mut camera = RtsCamera { looking_at, ..Default::default() };
+        camera.face_point(looking_at + Vec3::unit_y(), &settings.turn);
+        assert!(camera.tween.is_none());
+    }
+
+    #[test]
+    fn screen_centre_ground_follows_the_rendered_view() {
+        let settings = TestSettings::default();
+        let camera_component = perspective_camera();
+        let looking_at = Vec3::new(3.0, 0.0, -2.0);
+        let mut camera = RtsCamera { looking_at, ..Default::default() };
+        tick(&mut camera, &settings, &keys(&[]));
+        assert!(camera.screen_center_ground(&camera_component).unwrap().abs_diff_eq(looking_at, 1e-4));
+
+        // Raising the rendered focus aims the view past the target, onto the ground beyond it
+        camera.focus_height_offset = 2.0;
+        let view = camera.camera_transform();
+        let aim = looking_at + Vec3::unit_y() * 2.0;
+        let direction = (aim - view.translation).normalize();
+        let expected = view.translation + direction * (-view.translation.y / direction.y);
+
+        let centre = camera.screen_center_ground(&camera_component).unwrap();
+        assert!(centre.abs_diff_eq(expected, 1e-3), "expected {}, got {}", expected, centre);
+        assert!(centre.distance(looking_at) > 1.0);
+        assert_eq!(camera.looking_at, looking_at);
+    }
+
+    #[test]
+    fn tapping_a_pan_key_flicks_the_camera() {
+        let mut settings = TestSettings::default();
+        settings.pan.flick_impulse = 4.0;
+        let hold = settings.pan.keyboard_accel * DELTA;
+        let mut camera = RtsCamera::default();
+        let mut pan_accel = |keyboard: &Input<KeyCode>| {
+            camera.read_intent(None, None, VIEWPORT, keyboard, &settings.camera(), DELTA, 0.0).pan_accel
+        };
+
+        // The impulse is only given when the key goes down, on top of the usual acceleration
+        assert_close(pan_accel(&keys(&[KeyCode::Right])).x, hold + 4.0);
+        assert_close(pan_accel(&keys(&[KeyCode::Right])).x, hold);
+        assert_eq!(pan_accel(&keys(&[])), Vec2::zero());
+        assert_close(pan_accel(&keys(&[KeyCode::Right])).x, hold + 4.0);
+        assert_close(pan_accel(&keys(&[KeyCode::Right, KeyCode::Up])).y, hold + 4.0);
+
+        // With a low idle deceleration, a tap coasts
+        settings.pan.idle_deceleration = 0.5;
+        let mut camera = RtsCamera::default();
+        tick(&mut camera, &settings, &keys(&[KeyCode::Right]));
+        assert!(camera.pan_velocity.x > hold * 10.0);
+
+        for _ in 0..30 {
+            tick(&mut camera, &settings, &keys(&[]));
+        }
+
+        assert!(camera.pan_velocity.x > 3.0);
+    }
+
+    #[test]
+    fn panning_and_picking_stay_on_a_tilted_ground() {
+        let settings = TestSettings::default();
+        let ground = Plane::new(Vec3::new(0.0, 2.0, 0.0), Vec3::new(0.3, 1.0, 0.2).normalize());
+        let mut camera = RtsCamera { looking_at: ground.point, ground, ..Default::default() };
+
+        // Panning in both directions while turning keeps the target on the plane
+        for _ in 0..120 {
+            tick(&mut camera, &settings, &keys(&[KeyCode::Up, KeyCode::Right, KeyCode::Q]));
+            assert!(ground.distance_to(camera.looking_at).abs() < 1e-4, "left the plane at {}", camera.looking_at);
+        }
+
+        assert!(camera.looking_at.distance(ground.point) > 1.0);
+        assert!(camera.yaw > 0.5);
+
+        for _ in 0..120 {
+            tick(&mut camera, &settings, &keys(&[]));
+            assert!(ground.distance_to(camera.looking_at).abs() < 1e-4);
+        }
+
+        // The pan directions lie along the plane
+        let (right, ahead) = camera.motion_state().pan_basis();
+        assert!(right.dot(ground.normal).abs() < 1e-5 && ahead.dot(ground.normal).abs() < 1e-5);
+
+        // The cursor projects onto the plane, with the centre of the view at the target
+        let (window, camera_component) = (window(None), perspective_camera());
+        for &cursor in &[Vec2::new(100.0, 100.0), Vec2::new(1100.0, 300.0), VIEWPORT / 2.0] {
+            let point = camera.cursor_to_ground(cursor, &window, &camera_component).unwrap();
+            assert!(ground.distance_to(point).abs() < 1e-3, "{} is off the plane", point);
+        }
+
+        let centre = camera.cursor_to_ground(VIEWPORT / 2.0, &window, &camera_component).unwrap();
+        assert!(centre.abs_diff_eq(camera.looking_at, 1e-2));
+    }
 }
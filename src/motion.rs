@@ -0,0 +1,562 @@
+//! The motion model of the RTS camera, independent of Bevy's input handling. The camera system
+//! gathers input into an [`Intent`] each frame and integrates it into the [`MotionState`], but the
+//! motion model can also be driven directly, e.g by a tool previewing camera paths.
+
+use crate::util::{
+    clamp, ease_in_zone, lerp_in_zone, shortest_yaw_delta, DecelModel, Deceleration, Easing, Plane,
+};
+use crate::MIN_CAMERA_DISTANCE;
+use bevy::math::{Quat, Vec2, Vec3};
+use std::f32::consts::TAU;
+use std::ops::RangeInclusive;
+
+/// The pan speed below which the pan velocity is snapped to zero.
+const PAN_SPEED_EPSILON: f32 = 1e-6;
+
+/// How close the zoom distance must come to the distance range while easing back into it to snap
+/// into it.
+const ZOOM_RANGE_EPSILON: f32 = 1e-3;
+
+/// The input to the camera for a single frame, in terms of what it should do rather than which
+/// device asked for it.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Intent {
+    /// The change in pan velocity requested this frame, before sensitivity is applied. The x axis
+    /// is to the right, and the y axis is forward.
+    pub pan_accel: Vec2,
+    /// The change in zoom velocity requested this frame, before sensitivity is applied. Positive
+    /// values zoom out.
+    pub zoom_accel: f32,
+    /// The change in turn velocity requested this frame, before sensitivity is applied. Positive
+    /// values turn left.
+    pub turn_accel: f32,
+    /// A change in zoom distance to apply at once this frame, e.g from a discrete zoom step.
+    /// Positive values zoom out.
+    pub zoom_step: f32,
+    /// A change in yaw in radians to apply at once this frame, e.g from a discrete turn step.
+    /// Positive values turn left.
+    pub turn_step: f32,
+    /// The direction in which to adjust the pitch, where positive values pitch up and 1 is the
+    /// pitch settings' full speed. If this is `None`, there is no pitch input.
+    pub pitch: Option<f32>,
+    /// Which directions the horizontal pan velocity should be decelerated against
+    pub pan_x_decel: Deceleration,
+    /// Which directions the vertical pan velocity should be decelerated against
+    pub pan_y_decel: Deceleration,
+    /// Which directions the zoom velocity should be decelerated against
+    pub zoom_decel: Deceleration,
+    /// Which directions the turn velocity should be decelerated against
+    pub turn_decel: Deceleration,
+}
+
+impl Default for Intent {
+    fn default() -> Self {
+        Intent {
+            pan_accel: Vec2::zero(),
+            zoom_accel: 0.0,
+            turn_accel: 0.0,
+            zoom_step: 0.0,
+            turn_step: 0.0,
+            pitch: None,
+            pan_x_decel: Deceleration::default(),
+            pan_y_decel: Deceleration::default(),
+            zoom_decel: Deceleration::default(),
+            turn_decel: Deceleration::default(),
+        }
+    }
+}
+
+impl Intent {
+    /// Whether there is any pan, zoom, turn or pitch input.
+    pub fn is_active(&self) -> bool {
+        self.pan_accel != Vec2::zero()
+            || self.zoom_accel != 0.0
+            || self.turn_accel != 0.0
+            || self.zoom_step != 0.0
+            || self.turn_step != 0.0
+            || self.pitch.is_some()
+    }
+}
+
+/// The settings which the motion model uses. Unlike the camera's settings resources, these say
+/// nothing about which input devices or keys drive the camera.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MotionSettings<'a> {
+    pub zoom: ZoomMotion,
+    pub pan: PanMotion,
+    pub turn: TurnMotion<'a>,
+    pub pitch: PitchMotion,
+}
+
+/// The zoom settings which the motion model uses. The fields have the same meaning as the
+/// corresponding fields of [`ZoomSettings`](crate::ZoomSettings).
+#[derive(Clone, PartialEq, Debug)]
+pub struct ZoomMotion {
+    pub angle_range: RangeInclusive<f32>,
+    pub angle_change_zone: RangeInclusive<f32>,
+    pub invert_angle: bool,
+    pub distance_change_zone: Option<RangeInclusive<f32>>,
+    pub distance_range: RangeInclusive<f32>,
+    pub range_return_smoothing: f32,
+    pub max_velocity: f32,
+    pub idle_deceleration: f32,
+    pub idle_deceleration_in: Option<f32>,
+    pub idle_deceleration_out: Option<f32>,
+    pub decel_model: DecelModel,
+    pub sensitivity: f32,
+    pub pitch_smoothing: f32,
+    pub max_pitch_rate: Option<f32>,
+}
+
+impl ZoomMotion {
+    /// The pitch of the camera in radians at the given zoom distance, before any manual pitch
+    /// adjustment is applied.
+    pub fn pitch_at(&self, distance: f32) -> f32 {
+        if self.invert_angle {
+            let inverted = *self.angle_range.end()..=*self.angle_range.start();
+            lerp_in_zone(distance, &self.angle_change_zone, &inverted)
+        } else {
+            lerp_in_zone(distance, &self.angle_change_zone, &self.angle_range)
+        }
+    }
+
+    /// The distance of the camera from the target at the given zoom distance.
+    pub fn camera_distance_at(&self, zoom_distance: f32) -> f32 {
+        match &self.distance_change_zone {
+            Some(zone) => clamp(zoom_distance, zone),
+            None => zoom_distance,
+        }
+    }
+}
+
+/// The pan settings which the motion model uses. The fields have the same meaning as the
+/// corresponding fields of [`PanSettings`](crate::PanSettings).
+#[derive(Clone, PartialEq, Debug)]
+pub struct PanMotion {
+    pub max_speed: f32,
+    pub idle_deceleration: f32,
+    pub reverse_deceleration: Option<f32>,
+    pub decel_model: DecelModel,
+    pub sensitivity: Vec2,
+    pub pan_speed_zoom_factor_range: RangeInclusive<f32>,
+    pub pan_speed_zoom_factor_easing: Easing,
+}
+
+impl PanMotion {
+    /// The factor by which the pan speed is multiplied at the given zoom distance. See
+    /// [`PanSettings::speed_zoom_factor`](crate::PanSettings::speed_zoom_factor).
+    pub fn speed_zoom_factor(&self, zoom_distance: f32, zoom: &ZoomMotion) -> f32 {
+        ease_in_zone(
+            zoom_distance,
+            &zoom.distance_range,
+            &self.pan_speed_zoom_factor_range,
+            self.pan_speed_zoom_factor_easing,
+        )
+    }
+}
+
+/// The turn settings which the motion model uses. The fields have the same meaning as the
+/// corresponding fields of [`TurnSettings`](crate::TurnSettings).
+#[derive(Clone, PartialEq, Debug)]
+pub struct TurnMotion<'a> {
+    pub yaw_range: RangeInclusive<f32>,
+    pub yaw_sectors: Option<&'a [RangeInclusive<f32>]>,
+    pub max_speed: f32,
+    pub idle_deceleration: f32,
+    pub decel_model: DecelModel,
+    pub sensitivity: f32,
+}
+
+/// The pitch settings which the motion model uses. The fields have the same meaning as the
+/// corresponding fields of [`PitchSettings`](crate::PitchSettings).
+#[derive(Clone, PartialEq, Debug)]
+pub struct PitchMotion {
+    pub offset_range: RangeInclusive<f32>,
+    pub speed: f32,
+    pub auto_recenter: Option<f32>,
+}
+
+/// The position, orientation and velocities of the camera. The fields have the same meaning as
+/// the corresponding fields of [`RtsCamera`](crate::RtsCamera).
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct MotionState {
+    pub looking_at: Vec3,
+    pub rotation: Quat,
+    pub yaw: f32,
+    pub pitch_offset: f32,
+    pub zoom_pitch: Option<f32>,
+    pub zoom_distance: f32,
+    pub camera_distance: f32,
+    pub zoom_range_return: bool,
+    pub pan_velocity: Vec2,
+    pub zoom_velocity: f32,
+    pub turn_velocity: f32,
+    pub ground: Plane,
+}
+
+impl Default for MotionState {
+    fn default() -> Self {
+        MotionState {
+            looking_at: Vec3::zero(),
+            rotation: Quat::default(),
+            yaw: 0.0,
+            pitch_offset: 0.0,
+            zoom_pitch: None,
+            zoom_distance: 10.0,
+            camera_distance: 10.0,
+            zoom_range_return: false,
+            pan_velocity: Vec2::zero(),
+            zoom_velocity: 0.0,
+            turn_velocity: 0.0,
+            ground: Plane::default(),
+        }
+    }
+}
+
+impl MotionState {
+    /// The translation of the camera itself, as opposed to its target.
+    pub fn translation(&self) -> Vec3 {
+        self.looking_at + self.rotation * Vec3::new(0.0, 0.0, self.camera_distance)
+    }
+
+    /// Turn the camera in place by the given angle in radians.
+    pub fn rotate(&mut self, angle: f32) {
+        self.yaw += angle;
+
+        if self.yaw > TAU {
+            self.yaw -= TAU;
+        }
+
+        if self.yaw < 0.0 {
+            self.yaw += TAU;
+        }
+
+        let rotation_y = Quat::from_rotation_y(angle);
+        let camera_translation = self.translation();
+        let looking_at = (rotation_y * (self.looking_at - camera_translation)) + camera_translation;
+
+        // Turning about the vertical takes the target off tilted ground, so it is moved back to the
+        // same height above the ground
+        self.looking_at = self.ground.lift_to_distance(looking_at, self.ground.distance_to(self.looking_at));
+    }
+
+    /// The directions in world space in which the camera pans right and forward: those of the
+    /// camera on the XZ plane, moved into the ground plane keeping their length.
+    pub fn pan_basis(&self) -> (Vec3, Vec3) {
+        let forward = Quat::from_rotation_y(self.yaw);
+        let (right, ahead) = (forward * Vec3::unit_x(), forward * -Vec3::unit_z());
+        (self.ground.project_direction(right), self.ground.project_direction(ahead))
+    }
+
+    /// Advance the motion by `delta` seconds, applying the given intent.
+    pub fn integrate(&mut self, intent: &Intent, settings: &MotionSettings, delta: f32) {
+        let MotionSettings { zoom, pan, turn, pitch: pitch_settings } = settings;
+
+        self.pan_velocity += intent.pan_accel * pan.sensitivity;
+        self.zoom_velocity += intent.zoom_accel * zoom.sensitivity;
+        self.turn_velocity += intent.turn_accel * turn.sensitivity;
+
+        // Apply manual pitch adjustment, recentering it if there is no pitch input
+        match (intent.pitch, pitch_settings.auto_recenter) {
+            (Some(direction), _) => self.pitch_offset += pitch_settings.speed * direction * delta,
+            (None, Some(speed)) => {
+                let step = f32::min(speed * delta, self.pitch_offset.abs());
+                self.pitch_offset -= step * self.pitch_offset.signum();
+            }
+            (None, None) => {}
+        }
+
+        self.pitch_offset = clamp(self.pitch_offset, &pitch_settings.offset_range);
+
+        // Apply zoom/pan deceleration
+        let pan_reverse_decel = pan.reverse_deceleration.unwrap_or(pan.idle_deceleration);
+        let (turn_idle, zoom_idle, pan_idle) = (turn.idle_deceleration, zoom.idle_deceleration, pan.idle_deceleration);
+        intent.turn_decel.apply(&mut self.turn_velocity, turn_idle, turn_idle, turn.decel_model, delta);
+        let (zoom_idle_out, zoom_idle_in) = (
+            zoom.idle_deceleration_out.unwrap_or(zoom_idle),
+            zoom.idle_deceleration_in.unwrap_or(zoom_idle),
+        );
+        intent.zoom_decel.apply_asymmetric(
+            &mut self.zoom_velocity,
+            zoom_idle_out,
+            zoom_idle_in,
+            zoom_idle,
+            zoom.decel_model,
+            delta,
+        );
+        intent.pan_x_decel.apply(&mut self.pan_velocity.x, pan_idle, pan_reverse_decel, pan.decel_model, delta);
+        intent.pan_y_decel.apply(&mut self.pan_velocity.y, pan_idle, pan_reverse_decel, pan.decel_model, delta);
+
+        // Clamp velocity to max, snapping tiny velocities to zero so that the direction is never
+        // taken of a (near) zero vector
+        let pan_speed = self.pan_velocity.length();
+        if pan_speed < PAN_SPEED_EPSILON {
+            self.pan_velocity = Vec2::zero();
+        } else if pan_speed > pan.max_speed {
+            self.pan_velocity *= pan.max_speed / pan_speed;
+        }
+
+        self.zoom_velocity = clamp(self.zoom_velocity, &(-zoom.max_velocity..=zoom.max_velocity));
+        self.turn_velocity = clamp(self.turn_velocity, &(-turn.max_speed..=turn.max_speed));
+
+        // Apply zoom velocity
+        if !self.zoom_range_return || zoom.distance_range.contains(&self.zoom_distance) {
+            self.zoom_range_return = false;
+            self.zoom_distance += self.zoom_velocity * delta + intent.zoom_step;
+            self.zoom_distance = clamp(self.zoom_distance, &zoom.distance_range);
+
+            // Stop the velocity at the limits, so that it does not build up unseen while input pushes
+            // into the limit and the camera responds at once when the input reverses
+            if self.zoom_distance == *zoom.distance_range.start() || self.zoom_distance == *zoom.distance_range.end() {
+                self.zoom_velocity = 0.0;
+            }
+        } else {
+            // The distance range has changed under the camera, so ease back into it rather than
+            // snapping
+            let nearest = clamp(self.zoom_distance, &zoom.distance_range);
+            let factor = match zoom.range_return_smoothing {
+                smoothing if smoothing > 0.0 => 1.0 - (-delta / smoothing).exp(),
+                _ => 1.0,
+            };
+
+            self.zoom_distance += (nearest - self.zoom_distance) * factor;
+            self.zoom_velocity = 0.0;
+
+            if (nearest - self.zoom_distance).abs() < ZOOM_RANGE_EPSILON {
+                self.zoom_distance = nearest;
+                self.zoom_range_return = false;
+            }
+        }
+
+        self.camera_distance = f32::max(zoom.camera_distance_at(self.zoom_distance), MIN_CAMERA_DISTANCE);
+
+        // Apply turn velocity
+        match turn.yaw_sectors {
+            Some(sectors) => {
+                let new_yaw = (self.yaw + self.turn_velocity * delta + intent.turn_step).rem_euclid(TAU);
+                let allowed_yaw = constrain_to_sectors(new_yaw, sectors);
+
+                if allowed_yaw != new_yaw {
+                    self.turn_velocity = 0.0;
+                }
+
+                self.rotate(allowed_yaw - self.yaw);
+            }
+            None => {
+                self.rotate(self.turn_velocity * delta + intent.turn_step);
+                let clamped_yaw = clamp(self.yaw, &turn.yaw_range);
+
+                // As with zoom, stop the velocity at the limits so that reversing off them is instant
+                if clamped_yaw != self.yaw {
+                    self.yaw = clamped_yaw;
+                    self.turn_velocity = 0.0;
+                }
+            }
+        }
+
+        // Rotate camera angle depending on zoom (pitch) and yaw, easing the pitch towards that of the
+        // zoom distance if it is smoothed, and at no more than the maximum pitch rate
+        let target_pitch = zoom.pitch_at(self.zoom_distance);
+        let zoom_pitch = match self.zoom_pitch {
+            Some(current) if zoom.pitch_smoothing > 0.0 => {
+                current + (target_pitch - current) * (1.0 - (-delta / zoom.pitch_smoothing).exp())
+            }
+            _ => target_pitch,
+        };
+        let zoom_pitch = match (self.zoom_pitch, zoom.max_pitch_rate) {
+            (Some(current), Some(rate)) => {
+                let max_change = rate * delta;
+                current + clamp(zoom_pitch - current, &(-max_change..=max_change))
+            }
+            _ => zoom_pitch,
+        };
+
+        self.zoom_pitch = Some(zoom_pitch);
+        let pitch = zoom_pitch + self.pitch_offset;
+        self.rotation = Quat::from_rotation_ypr(self.yaw, -pitch, 0.0);
+
+        // Apply pan velocity, taking into account the rotation of the camera, along the ground plane
+        let (right, ahead) = self.pan_basis();
+        let distance_factor = pan.speed_zoom_factor(self.zoom_distance, zoom);
+        self.looking_at += right * self.pan_velocity.x * delta * distance_factor;
+        self.looking_at += ahead * self.pan_velocity.y * delta * distance_factor;
+    }
+}
+
+/// Constrain a yaw within 0 and TAU to the nearest of the given sectors, measuring distance around
+/// the circle.
+fn constrain_to_sectors(yaw: f32, sectors: &[RangeInclusive<f32>]) -> f32 {
+    if sectors.is_empty() || sectors.iter().any(|sector| sector.contains(&yaw)) {
+        return yaw;
+    }
+
+    let arc_distance = |to: f32| shortest_yaw_delta(yaw, to).abs();
+
+    sectors
+        .iter()
+        .flat_map(|sector| [*sector.start(), *sector.end()])
+        .min_by(|a, b| arc_distance(*a).partial_cmp(&arc_distance(*b)).unwrap())
+        .unwrap()
+}
+
+/// Constrain a yaw to that which the turn settings allow: the nearest of the `yaw_sectors` if they
+/// are set, and otherwise the `yaw_range`.
+pub(crate) fn constrain_yaw(yaw: f32, turn: &TurnMotion) -> f32 {
+    let yaw = yaw.rem_euclid(TAU);
+
+    match turn.yaw_sectors {
+        Some(sectors) => constrain_to_sectors(yaw, sectors),
+        None => clamp(yaw, &turn.yaw_range),
+    }
+}
+
+/// The signed angle by which to turn from one allowed yaw to another without passing through yaw
+/// which the turn settings do not allow. This is the short way around unless only the long way
+/// stays within the allowed yaw.
+pub(crate) fn allowed_yaw_delta(from: f32, to: f32, turn: &TurnMotion) -> f32 {
+    let short = shortest_yaw_delta(from, to);
+    let long = if short > 0.0 { short - TAU } else { short + TAU };
+
+    if short != 0.0 && !arc_allowed(from, short, turn) && arc_allowed(from, long, turn) {
+        long
+    } else {
+        short
+    }
+}
+
+/// Whether turning from the given yaw by the given signed angle stays within the allowed yaw.
+fn arc_allowed(from: f32, delta: f32, turn: &TurnMotion) -> bool {
+    // The arc as the angle turned left from its start
+    let (start, length) = if delta >= 0.0 { (from, delta) } else { (from + delta, -delta) };
+    let start = start.rem_euclid(TAU);
+
+    disallowed_yaw(turn).iter().all(|(gap_start, gap_length)| {
+        let gap_in_arc = (gap_start - start).rem_euclid(TAU) < length;
+        let start_offset = (start - gap_start).rem_euclid(TAU);
+        let start_in_gap = start_offset > 0.0 && start_offset < *gap_length;
+        !gap_in_arc && !start_in_gap
+    })
+}
+
+/// The gaps of yaw which the turn settings do not allow, as their start and the angle from there
+/// to their end turning left. The gaps exclude their ends.
+fn disallowed_yaw(turn: &TurnMotion) -> Vec<(f32, f32)> {
+    let mut allowed = match turn.yaw_sectors {
+        Some(sectors) => sectors.iter().map(|sector| (*sector.start(), *sector.end())).collect(),
+        None => {
+            let (start, end) = (turn.yaw_range.start().max(0.0), turn.yaw_range.end().min(TAU));
+            vec![(start, end)]
+        }
+    };
+
+    if allowed.is_empty() {
+        return Vec::new();
+    }
+
+    allowed.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    let next_starts = allowed.iter().skip(1).map(|(start, _)| *start).chain(Some(allowed[0].0 + TAU));
+    allowed
+        .iter()
+        .zip(next_starts)
+        .map(|((_, end), next_start)| (*end, next_start - end))
+        .filter(|(_, length)| *length > 0.0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TestSettings;
+
+    /// An intent with no input, which does not decelerate the turn.
+    fn coasting_turn() -> Intent {
+        Intent { turn_decel: Deceleration { pos: false, neg: false }, ..Default::default() }
+    }
+
+    #[test]
+    fn turning_into_a_gap_snaps_to_the_nearest_sector() {
+        let mut settings = TestSettings::default();
+        settings.turn.yaw_sectors = Some(vec![0.0..=1.0, 2.0..=3.0]);
+
+        let mut state = MotionState { yaw: 0.9, turn_velocity: 1.5, ..Default::default() };
+        state.integrate(&coasting_turn(), &settings.motion(), 0.5);
+        assert_eq!(state.yaw, 2.0);
+        assert_eq!(state.turn_velocity, 0.0);
+
+        let mut state = MotionState { yaw: 0.9, turn_velocity: 1.0, ..Default::default() };
+        state.integrate(&coasting_turn(), &settings.motion(), 0.5);
+        assert_eq!(state.yaw, 1.0);
+        assert_eq!(state.turn_velocity, 0.0);
+
+        // Within a sector, the camera turns freely
+        let mut state = MotionState { yaw: 2.1, turn_velocity: 1.0, ..Default::default() };
+        state.integrate(&coasting_turn(), &settings.motion(), 0.5);
+        assert!((state.yaw - 2.6).abs() < 1e-5);
+        assert_eq!(state.turn_velocity, 1.0);
+    }
+
+    #[test]
+    fn integrating_an_intent_moves_the_camera() {
+        let settings = TestSettings::default();
+        let intent = Intent {
+            pan_accel: Vec2::new(1.0, 0.0),
+            zoom_accel: 2.0,
+            pan_x_decel: Deceleration { pos: false, neg: false },
+            zoom_decel: Deceleration { pos: false, neg: false },
+            ..Default::default()
+        };
+        assert!(intent.is_active());
+        assert!(!Intent::default().is_active());
+
+        let mut state = MotionState::default();
+        state.integrate(&intent, &settings.motion(), 0.5);
+
+        assert_eq!(state.pan_velocity, Vec2::new(1.0, 0.0));
+        assert!(state.looking_at.x > 0.0);
+        assert_eq!((state.looking_at.y, state.looking_at.z), (0.0, 0.0));
+        assert_eq!(state.zoom_velocity, 2.0);
+        assert_eq!(state.zoom_distance, 11.0);
+
+        let pitch = settings.zoom.pitch_at(11.0);
+        assert!(state.rotation.abs_diff_eq(Quat::from_rotation_ypr(0.0, -pitch, 0.0), 1e-6));
+    }
+
+    #[test]
+    fn idle_intents_decelerate_to_rest() {
+        let settings = TestSettings::default();
+        let mut state = MotionState { pan_velocity: Vec2::new(3.0, -2.0), turn_velocity: 1.0, ..Default::default() };
+
+        for _ in 0..60 {
+            state.integrate(&Intent::default(), &settings.motion(), 1.0 / 60.0);
+        }
+
+        assert_eq!(state.pan_velocity, Vec2::zero());
+        assert_eq!(state.turn_velocity, 0.0);
+        let settled = state;
+        state.integrate(&Intent::default(), &settings.motion(), 1.0 / 60.0);
+        assert_eq!(state, settled);
+    }
+
+    #[test]
+    fn tiny_pan_velocities_snap_to_rest() {
+        let settings = TestSettings::default();
+        let coasting = Intent {
+            pan_x_decel: Deceleration { pos: false, neg: false },
+            pan_y_decel: Deceleration { pos: false, neg: false },
+            ..Default::default()
+        };
+
+        for &speed in &[PAN_SPEED_EPSILON / 2.0, f32::MIN_POSITIVE, 1e-30] {
+            let mut state = MotionState { pan_velocity: Vec2::new(speed, -speed), ..Default::default() };
+            state.integrate(&coasting, &settings.motion(), 0.5);
+            assert_eq!(state.pan_velocity, Vec2::zero());
+            assert!(state.looking_at.is_finite());
+        }
+
+        // Velocities above the threshold keep coasting
+        let mut state = MotionState { pan_velocity: Vec2::new(0.01, 0.0), ..Default::default() };
+        state.integrate(&coasting, &settings.motion(), 0.5);
+        assert_eq!(state.pan_velocity, Vec2::new(0.01, 0.0));
+    }
+}
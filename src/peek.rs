@@ -0,0 +1,99 @@
+//! Glancing at a point while a key is held, then returning.
+
+use crate::{CameraState, RtsCamera, PLAYER_PRIORITY};
+use bevy::prelude::*;
+use std::borrow::Cow;
+
+/// A component which, when attached to the camera entity, makes the camera smoothly move to look
+/// at `target` while any of the peek keys are held, e.g to glance at an alert, and smoothly return
+/// to where it was when they are released. If the keys are released before the camera arrives, it
+/// turns back from wherever it has got to.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Peek {
+    /// The point to look at while peeking
+    pub target: Vec3,
+    /// The keys which make the camera peek while held
+    pub keys: Cow<'static, [KeyCode]>,
+    /// The duration in seconds of the moves to and from the target
+    pub duration: f32,
+    /// The pose of the camera before the current peek started, which it returns to on release.
+    /// This is `None` while not peeking.
+    pub origin: Option<CameraState>,
+}
+
+impl Peek {
+    pub fn new(target: Vec3, keys: impl Into<Cow<'static, [KeyCode]>>) -> Self {
+        Peek {
+            target,
+            keys: keys.into(),
+            duration: 0.3,
+            origin: None,
+        }
+    }
+
+    /// Start or end the peek according to the peek keys.
+    pub(crate) fn update(&mut self, camera: &mut RtsCamera, keyboard: &Input<KeyCode>) {
+        let held = camera.wants_input
+            && camera.accepts(PLAYER_PRIORITY)
+            && self.keys.iter().any(|c| keyboard.pressed(*c));
+
+        match (held, self.origin) {
+            (true, None) => {
+                let origin = camera.state();
+                self.origin = Some(origin);
+                camera.tween_to(CameraState { looking_at: self.target, ..origin }, self.duration);
+            }
+            (false, Some(origin)) => {
+                self.origin = None;
+                camera.tween_to(origin, self.duration);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TestApp;
+
+    #[test]
+    fn peeking_returns_to_the_origin_on_release() {
+        let mut app = TestApp::new();
+        let origin = Vec3::new(5.0, 0.0, 5.0);
+        let target = Vec3::new(-20.0, 0.0, 30.0);
+        let camera = RtsCamera { looking_at: origin, ..Default::default() };
+        let peek = Peek::new(target, &[KeyCode::Space][..]);
+        let entity = app.world.spawn((camera, Transform::default(), peek));
+        let looking_at = |app: &TestApp| app.world.get::<RtsCamera>(entity).unwrap().looking_at;
+        let hold = |app: &mut TestApp, held: bool, frames: u32| {
+            let mut keyboard = app.resources.get_mut::<Input<KeyCode>>().unwrap();
+
+            if held {
+                keyboard.press(KeyCode::Space);
+            } else {
+                keyboard.release(KeyCode::Space);
+            }
+
+            drop(keyboard);
+
+            for _ in 0..frames {
+                app.update();
+            }
+        };
+
+        hold(&mut app, true, 30);
+        assert!(looking_at(&app).abs_diff_eq(target, 1e-4));
+        assert!(app.world.get::<Peek>(entity).unwrap().origin.is_some());
+
+        hold(&mut app, false, 30);
+        assert!(looking_at(&app).abs_diff_eq(origin, 1e-4));
+        assert_eq!(app.world.get::<Peek>(entity).unwrap().origin, None);
+
+        // A brief peek turns back from part of the way there
+        hold(&mut app, true, 3);
+        assert!(looking_at(&app) != origin);
+        hold(&mut app, false, 30);
+        assert!(looking_at(&app).abs_diff_eq(origin, 1e-4));
+    }
+}
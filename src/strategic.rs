@@ -0,0 +1,236 @@
+//! Switching between the tactical camera and a top-down strategic view of the map.
+
+use crate::{CameraControls, CameraState, RtsCamera, SettingsOverride, ZoomSettings, PLAYER_PRIORITY};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use std::borrow::Cow;
+use std::f32::consts::FRAC_PI_2;
+
+/// A component which, when attached to the camera entity, makes the strategic view keys toggle the
+/// camera between its usual view and a top-down view from high above, as in many RTS games. The
+/// camera smoothly moves into the strategic view, and back to where it was when it is toggled off.
+///
+/// While in the strategic view, the camera uses zoom settings which fix its pitch and distance,
+/// pushed as a settings override, its controls are limited to `controls`, and its manual pitch
+/// offset is cleared so that the view is not tilted past `pitch`, to be restored on return. The
+/// override is popped again once the camera has returned, so other overrides should not be pushed
+/// or popped in the meantime. If the component is removed while the view is active, the override
+/// is popped and the controls and pitch offset are restored, but the camera does not move back.
+#[derive(Clone, PartialEq, Debug)]
+pub struct StrategicView {
+    /// The keys which toggle the strategic view when pressed
+    pub keys: Cow<'static, [KeyCode]>,
+    /// The zoom distance of the camera in the strategic view
+    pub zoom_distance: f32,
+    /// The pitch of the camera in radians in the strategic view, where a pitch of `FRAC_PI_2` looks
+    /// straight down
+    pub pitch: f32,
+    /// The duration in seconds of the moves into and out of the strategic view
+    pub duration: f32,
+    /// The time constant in seconds with which the pitch eases into and out of the strategic view
+    pub pitch_smoothing: f32,
+    /// The controls which the player has while in the strategic view
+    pub controls: CameraControls,
+    /// The pose, controls and manual pitch offset of the camera before the strategic view was
+    /// entered, which it returns to when it is toggled off. This is `None` while not in the
+    /// strategic view.
+    pub origin: Option<(CameraState, CameraControls, f32)>,
+    /// Whether the camera is moving back out of the strategic view. This is updated by the system
+    /// and should not be modified directly.
+    pub returning: bool,
+}
+
+impl StrategicView {
+    pub fn new(keys: impl Into<Cow<'static, [KeyCode]>>, zoom_distance: f32) -> Self {
+        StrategicView {
+            keys: keys.into(),
+            zoom_distance,
+            pitch: FRAC_PI_2,
+            duration: 0.5,
+            pitch_smoothing: 0.15,
+            controls: CameraControls::PAN,
+            origin: None,
+            returning: false,
+        }
+    }
+
+    /// Whether the camera is in the strategic view, or moving into it.
+    pub fn is_active(&self) -> bool {
+        self.origin.is_some()
+    }
+
+    /// Toggle the strategic view according to the strategic view keys, given the camera's zoom
+    /// settings outside of the strategic view.
+    pub(crate) fn update(&mut self, camera: &mut RtsCamera, keyboard: &Input<KeyCode>, zoom: &ZoomSettings) {
+        // Once the camera is back, or the move back was interrupted, use its own settings again
+        if self.returning && camera.tween.is_none() {
+            self.returning = false;
+            camera.pop_settings_override();
+        }
+
+        let toggled = camera.wants_input
+            && camera.accepts(PLAYER_PRIORITY)
+            && self.keys.iter().any(|c| keyboard.just_pressed(*c));
+
+        if !toggled {
+            return;
+        }
+
+        if self.returning {
+            self.returning = false;
+            camera.pop_settings_override();
+        }
+
+        match self.origin.take() {
+            None => {
+                let origin = camera.state();
+                self.origin = Some((origin, camera.controls, camera.pitch_offset));
+                set_pitch_offset(camera, 0.0);
+
+                let strategic_zoom = ZoomSettings {
+                    angle_range: self.pitch..=self.pitch,
+                    distance_change_zone: None,
+                    ..self.transition_zoom(zoom)
+                };
+
+                camera.push_settings_override(SettingsOverride { zoom: Some(strategic_zoom), ..Default::default() });
+                camera.controls = self.controls;
+                camera.tween_to(CameraState { zoom_distance: self.zoom_distance, ..origin }, self.duration);
+            }
+            Some((origin, controls, pitch_offset)) => {
+                set_pitch_offset(camera, pitch_offset);
+                camera.pop_settings_override();
+                camera.push_settings_override(SettingsOverride {
+                    zoom: Some(self.transition_zoom(zoom)),
+                    ..Default::default()
+                });
+
+                self.returning = true;
+                camera.controls = controls;
+                camera.tween_to(origin, self.duration);
+            }
+        }
+    }
+
+    /// The given zoom settings, with the pitch smoothed and the distance range extended to reach the
+    /// strategic view, for moving between the views.
+    fn transition_zoom(&self, zoom: &ZoomSettings) -> ZoomSettings {
+        let range = &zoom.distance_range;
+
+        ZoomSettings {
+            pitch_smoothing: self.pitch_smoothing,
+            distance_range: range.start().min(self.zoom_distance)..=range.end().max(self.zoom_distance),
+            ..zoom.clone()
+        }
+    }
+}
+
+/// Set the manual pitch offset of the camera, adjusting the zoom pitch to make up the difference so
+/// that the pitch eases to its new value rather than jumping.
+fn set_pitch_offset(camera: &mut RtsCamera, pitch_offset: f32) {
+    if let Some(zoom_pitch) = &mut camera.zoom_pitch {
+        *zoom_pitch += camera.pitch_offset - pitch_offset;
+    }
+
+    camera.pitch_offset = pitch_offset;
+}
+
+/// A system which restores a camera whose [`StrategicView`] component is removed while the view is
+/// active or the camera is returning from it, popping the settings override which the view pushed
+/// and restoring the controls and pitch offset from before the view. This is added by the camera
+/// plugins, in the post-update stage so that removals in the update stage are seen.
+pub fn strategic_view_removal_system(
+    mut active: Local<HashMap<Entity, Option<(CameraControls, f32)>>>,
+    views: Query<(Entity, &StrategicView)>,
+    mut cameras: Query<&mut RtsCamera>,
+) {
+    for entity in views.removed::<StrategicView>() {
+        if let (Some(restore), Ok(mut camera)) = (active.remove(entity), cameras.get_mut(*entity)) {
+            camera.pop_settings_override();
+
+            if let Some((controls, pitch_offset)) = restore {
+                camera.controls = controls;
+                set_pitch_offset(&mut camera, pitch_offset);
+            }
+        }
+    }
+
+    // Remember which views have pushed an override, and what to restore, for when they are removed
+    active.clear();
+    for (entity, view) in views.iter() {
+        match view.origin {
+            Some((_, controls, pitch_offset)) => {
+                active.insert(entity, Some((controls, pitch_offset)));
+            }
+            None if view.returning => {
+                active.insert(entity, None);
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TestApp;
+    use bevy::ecs::Stage;
+
+    #[test]
+    fn toggling_moves_into_the_strategic_view_and_back() {
+        let mut app = TestApp::new();
+        let mut removal = SystemStage::serial().with_system(strategic_view_removal_system.system());
+        let camera = RtsCamera { looking_at: Vec3::new(3.0, 0.0, -2.0), yaw: 1.0, ..Default::default() };
+        let view = StrategicView::new(&[KeyCode::M][..], 200.0);
+        let entity = app.world.spawn((camera, Transform::default(), view));
+        let mut run = |app: &mut TestApp, frames: usize| {
+            for _ in 0..frames {
+                app.update();
+                removal.initialize(&mut app.world, &mut app.resources);
+                removal.run(&mut app.world, &mut app.resources);
+            }
+        };
+        let toggle = |app: &mut TestApp| app.resources.get_mut::<Input<KeyCode>>().unwrap().press(KeyCode::M);
+        let release = |app: &mut TestApp| app.resources.get_mut::<Input<KeyCode>>().unwrap().release(KeyCode::M);
+
+        run(&mut app, 1);
+        let origin = app.world.get::<RtsCamera>(entity).unwrap().state();
+        let pitch = |camera: &RtsCamera| camera.zoom_pitch.unwrap() + camera.pitch_offset;
+        let tactical_pitch = pitch(app.world.get::<RtsCamera>(entity).unwrap());
+
+        toggle(&mut app);
+        run(&mut app, 90);
+        release(&mut app);
+        {
+            let camera = app.world.get::<RtsCamera>(entity).unwrap();
+            assert_eq!(camera.state(), CameraState { zoom_distance: 200.0, ..origin });
+            assert!((pitch(camera) - FRAC_PI_2).abs() < 1e-3);
+            assert_eq!(camera.controls, CameraControls::PAN);
+            assert_eq!(camera.settings_overrides.len(), 1);
+        }
+
+        assert!(app.world.get::<StrategicView>(entity).unwrap().is_active());
+        run(&mut app, 1);
+        toggle(&mut app);
+        run(&mut app, 90);
+        release(&mut app);
+        {
+            let camera = app.world.get::<RtsCamera>(entity).unwrap();
+            assert_eq!(camera.state(), origin);
+            assert!((pitch(camera) - tactical_pitch).abs() < 1e-3);
+            assert_eq!(camera.controls, CameraControls::ALL);
+            assert!(camera.settings_overrides.is_empty());
+        }
+
+        // Removing the view while it is active restores the camera's settings and controls
+        assert!(!app.world.get::<StrategicView>(entity).unwrap().is_active());
+        run(&mut app, 1);
+        toggle(&mut app);
+        run(&mut app, 10);
+        app.world.remove_one::<StrategicView>(entity).unwrap();
+        removal.run(&mut app.world, &mut app.resources);
+        let camera = app.world.get::<RtsCamera>(entity).unwrap();
+        assert_eq!(camera.controls, CameraControls::ALL);
+        assert!(camera.settings_overrides.is_empty());
+    }
+}
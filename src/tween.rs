@@ -0,0 +1,150 @@
+//! Smoothly moving the camera between poses.
+
+use crate::util::{shortest_yaw_delta, Easing};
+use crate::{ZoomSettings, PLAYER_PRIORITY};
+use bevy::math::{Mat4, Quat, Vec3};
+use bevy::transform::components::Transform;
+use std::f32::consts::TAU;
+
+/// The pose of the camera: where it is looking, which way it is facing and how far it is zoomed
+/// out.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CameraState {
+    pub looking_at: Vec3,
+    pub yaw: f32,
+    pub zoom_distance: f32,
+}
+
+impl CameraState {
+    /// The pose the fraction `t` of the way from this pose to `other`, e.g for blending between
+    /// shots in cutscene tooling. The yaw is interpolated the short way around the circle.
+    pub fn lerp(&self, other: &CameraState, t: f32) -> CameraState {
+        CameraState {
+            looking_at: self.looking_at.lerp(other.looking_at, t),
+            yaw: (self.yaw + shortest_yaw_delta(self.yaw, other.yaw) * t).rem_euclid(TAU),
+            zoom_distance: self.zoom_distance + (other.zoom_distance - self.zoom_distance) * t,
+        }
+    }
+
+    /// Like [`CameraState::lerp`], but with the progress `t` eased along the given curve, which
+    /// clamps it to `0.0..=1.0`. Tweens use [`Easing::SmoothStep`].
+    pub fn lerp_eased(&self, other: &CameraState, t: f32, easing: Easing) -> CameraState {
+        self.lerp(other, easing.apply(t))
+    }
+
+    /// The transform of a camera in this pose with the given zoom settings, which determine its
+    /// pitch and distance from the target. Any manual pitch offset from the pitch settings is not
+    /// included.
+    pub fn transform(&self, zoom: &ZoomSettings) -> Transform {
+        let rotation = Quat::from_rotation_ypr(self.yaw, -zoom.pitch_at(self.zoom_distance), 0.0);
+        let distance = zoom.camera_distance_at(self.zoom_distance);
+        let translation = self.looking_at + rotation * Vec3::new(0.0, 0.0, distance);
+        Transform::from_matrix(Mat4::from_rotation_translation(rotation, translation))
+    }
+}
+
+/// A smooth move of the camera from one pose to another over a duration, eased in and out. Unless
+/// given otherwise with [`Tween::with_yaw_delta`], the yaw is interpolated the short way around the
+/// circle.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Tween {
+    pub from: CameraState,
+    pub to: CameraState,
+    /// The signed angle in radians by which the yaw turns over the move, where positive angles turn
+    /// left
+    pub yaw_delta: f32,
+    /// The duration of the move in seconds
+    pub duration: f32,
+    /// The time in seconds since the move started
+    pub elapsed: f32,
+    /// The priority of the move, which is [`PLAYER_PRIORITY`] unless given otherwise with
+    /// [`Tween::with_priority`]. The move is cancelled if the camera is held at a higher priority.
+    /// See [`RtsCamera::acquire`](crate::RtsCamera::acquire).
+    pub priority: u32,
+}
+
+impl Tween {
+    pub fn new(from: CameraState, to: CameraState, duration: f32) -> Self {
+        let yaw_delta = shortest_yaw_delta(from.yaw, to.yaw);
+        Tween { from, to, yaw_delta, duration, elapsed: 0.0, priority: PLAYER_PRIORITY }
+    }
+
+    /// Make the move at the given priority, e.g that at which a scripted move holds the camera.
+    pub fn with_priority(self, priority: u32) -> Self {
+        Tween { priority, ..self }
+    }
+
+    /// Turn by the given signed angle over the move rather than the short way around, e.g to avoid
+    /// yaw which the camera may not turn through. The angle should take the yaw from that of the
+    /// start pose to that of the end pose.
+    pub fn with_yaw_delta(self, yaw_delta: f32) -> Self {
+        Tween { yaw_delta, ..self }
+    }
+
+    /// Whether the move has reached its destination.
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// The pose at the current point in the move.
+    pub fn current(&self) -> CameraState {
+        let t = if self.duration > 0.0 {
+            (self.elapsed / self.duration).min(1.0)
+        } else {
+            1.0
+        };
+
+        let t = Easing::SmoothStep.apply(t);
+        CameraState {
+            yaw: (self.from.yaw + self.yaw_delta * t).rem_euclid(TAU),
+            ..self.from.lerp(&self.to, t)
+        }
+    }
+
+    /// Advance the move by `delta` seconds, returning the new pose.
+    pub(crate) fn advance(&mut self, delta: f32) -> CameraState {
+        self.elapsed += delta;
+        self.current()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tweens_turn_the_short_way_around() {
+        let pose = |yaw| CameraState { looking_at: Vec3::zero(), yaw, zoom_distance: 10.0 };
+        let mut tween = Tween::new(pose(0.1), pose(TAU - 0.1), 1.0);
+        assert!((tween.yaw_delta + 0.2).abs() < 1e-5);
+
+        // Halfway, the camera faces forward rather than backward
+        let halfway = tween.advance(0.5).yaw;
+        assert!(shortest_yaw_delta(halfway, 0.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn lerping_matches_the_endpoints_and_blends_between() {
+        let zoom = ZoomSettings::default();
+        let from = CameraState { looking_at: Vec3::new(0.0, 0.0, 0.0), yaw: 0.3, zoom_distance: 10.0 };
+        let to = CameraState { looking_at: Vec3::new(10.0, 0.0, -4.0), yaw: TAU - 0.5, zoom_distance: 30.0 };
+
+        assert_eq!(from.lerp(&to, 0.0), from);
+        let end = from.lerp(&to, 1.0);
+        assert!(end.looking_at.abs_diff_eq(to.looking_at, 1e-5));
+        assert!(shortest_yaw_delta(end.yaw, to.yaw).abs() < 1e-5);
+        assert!((end.zoom_distance - to.zoom_distance).abs() < 1e-5);
+        assert!(from.lerp(&to, 1.0).transform(&zoom).translation.abs_diff_eq(to.transform(&zoom).translation, 1e-4));
+
+        // Halfway, the yaw is between the two the short way around, through zero
+        let mid = from.lerp(&to, 0.5);
+        assert_eq!(mid.looking_at, Vec3::new(5.0, 0.0, -2.0));
+        assert!(shortest_yaw_delta(mid.yaw, TAU - 0.1).abs() < 1e-5);
+        assert_eq!(mid.zoom_distance, 20.0);
+
+        // Easing shapes the progress, but not the endpoints
+        assert_eq!(from.lerp_eased(&to, 0.5, Easing::SmoothStep), mid);
+        assert_eq!(from.lerp_eased(&to, 0.25, Easing::EaseIn), from.lerp(&to, Easing::EaseIn.apply(0.25)));
+        assert_eq!(from.lerp_eased(&to, -1.0, Easing::Linear), from);
+    }
+}
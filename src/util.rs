@@ -0,0 +1,504 @@
+//! The motion primitives used by the RTS camera, exposed so that other camera modes can share the
+//! same acceleration and deceleration feel.
+
+use bevy::math::Vec3;
+use std::borrow::Cow;
+use std::f32::consts::{PI, TAU};
+use std::ops::RangeInclusive;
+
+/// The way in which deceleration is applied to a velocity over time.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DecelModel {
+    /// The velocity is reduced by a constant amount per second, given by the deceleration value.
+    /// This comes to a definite stop, but high velocities take a long time to bleed off.
+    Linear,
+    /// The velocity is multiplied by `exp(-k * dt)` every frame, where `k` is the deceleration
+    /// value. This slows down quickly from high velocities and stops smoothly and asymptotically.
+    Exponential,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for DecelModel {
+    fn default() -> Self {
+        DecelModel::Linear
+    }
+}
+
+/// Which directions of motion a velocity should be decelerated against this frame. Input which
+/// accelerates the velocity in a direction should clear the flag for that direction, so that
+/// deceleration only opposes it when there is no input or the input opposes the current motion.
+///
+/// # Example
+///
+/// ```
+/// use goshawk::util::{Deceleration, DecelModel};
+///
+/// // No input, so decelerate towards zero from either direction
+/// let mut velocity = 2.0;
+/// Deceleration::default().apply(&mut velocity, 1.0, 1.0, DecelModel::Linear, 0.5);
+/// assert_eq!(velocity, 1.5);
+///
+/// // Input accelerating in the positive direction while moving in the negative direction, so
+/// // the reverse deceleration is used
+/// let mut velocity = -1.5;
+/// let decel = Deceleration { pos: false, neg: true };
+/// decel.apply(&mut velocity, 1.0, 4.0, DecelModel::Linear, 0.25);
+/// assert_eq!(velocity, -0.5);
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Deceleration {
+    /// Decelerate against motion in the positive direction
+    pub pos: bool,
+    /// Decelerate against motion in the negative direction
+    pub neg: bool,
+}
+
+impl Default for Deceleration {
+    fn default() -> Self {
+        Deceleration { pos: true, neg: true }
+    }
+}
+
+impl Deceleration {
+    /// Apply the deceleration to the given velocity. `idle` is the magnitude used when nothing is
+    /// accelerating the velocity, and `reverse` is the magnitude used when input is accelerating it
+    /// against its current direction. With the linear model, the velocity never overshoots zero.
+    pub fn apply(&self, velocity: &mut f32, idle: f32, reverse: f32, model: DecelModel, delta: f32) {
+        self.apply_asymmetric(velocity, idle, idle, reverse, model, delta);
+    }
+
+    /// Apply the deceleration to the given velocity like [`Deceleration::apply`], but with separate
+    /// idle magnitudes for positive and negative velocities, e.g to coast differently when zooming
+    /// in and out.
+    pub fn apply_asymmetric(
+        &self,
+        velocity: &mut f32,
+        idle_pos: f32,
+        idle_neg: f32,
+        reverse: f32,
+        model: DecelModel,
+        delta: f32,
+    ) {
+        if *velocity == 0.0 {
+            return;
+        }
+
+        let idle = if *velocity > 0.0 { idle_pos } else { idle_neg };
+
+        let (signum, magnitude) = if self.pos && self.neg {
+            (-velocity.signum(), idle)
+        } else if self.pos {
+            (-1.0, if *velocity > 0.0 { reverse } else { idle })
+        } else if self.neg {
+            (1.0, if *velocity < 0.0 { reverse } else { idle })
+        } else {
+            return; // no deceleration required
+        };
+
+        if model == DecelModel::Exponential {
+            // Exponential decay only ever brings the velocity towards zero
+            if signum == -velocity.signum() {
+                *velocity *= (-magnitude * delta).exp();
+            }
+
+            return;
+        }
+
+        let max_decel = magnitude * delta;
+        let decel_magnitude = f32::min(max_decel.abs(), velocity.abs());
+
+        *velocity += decel_magnitude * signum;
+    }
+}
+
+/// A curve which maps progress through a transition from 0 to 1 onto the fraction of the change
+/// applied, from 0 to 1.
+///
+/// # Example
+///
+/// ```
+/// use goshawk::util::Easing;
+///
+/// assert_eq!(Easing::Linear.apply(0.25), 0.25);
+/// assert_eq!(Easing::SmoothStep.apply(0.5), 0.5);
+/// assert!(Easing::SmoothStep.apply(0.25) < 0.25);
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Easing {
+    /// The change is applied at a constant rate
+    Linear,
+    /// The change starts slowly and speeds up
+    EaseIn,
+    /// The change starts quickly and slows down
+    EaseOut,
+    /// The change starts and ends slowly, with no abrupt change in rate at either end
+    SmoothStep,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for Easing {
+    fn default() -> Self {
+        Easing::Linear
+    }
+}
+
+impl Easing {
+    /// The fraction of the change applied at the given progress, which is clamped to `0.0..=1.0`.
+    #[must_use = "apply returns the new value and does not modify the original"]
+    pub fn apply(self, t: f32) -> f32 {
+        let t = clamp(t, &(0.0..=1.0));
+
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::SmoothStep => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// A curve which shapes the magnitude of an input from 0 to 1 before it scales the acceleration,
+/// e.g to make edge panning gentle near the inside of the margin and fast at the very edge.
+///
+/// # Example
+///
+/// ```
+/// use goshawk::util::ResponseCurve;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(ResponseCurve::Constant.apply(0.25), 1.0);
+/// assert_eq!(ResponseCurve::Linear.apply(0.25), 0.25);
+/// assert_eq!(ResponseCurve::Quadratic.apply(0.5), 0.25);
+///
+/// let curve = ResponseCurve::Sampled(Cow::Borrowed(&[0.0, 0.2, 1.0]));
+/// assert!((curve.apply(0.25) - 0.1).abs() < 1e-6);
+/// assert!((curve.apply(0.75) - 0.6).abs() < 1e-6);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum ResponseCurve {
+    /// Any input at all gives the full response
+    Constant,
+    /// The response is proportional to the input
+    Linear,
+    /// The response is the square of the input, giving finer control at small magnitudes
+    Quadratic,
+    /// The response is interpolated linearly between the given samples, which are spaced evenly
+    /// over the input from 0 to 1. With no samples, the response is linear.
+    Sampled(Cow<'static, [f32]>),
+}
+
+impl ResponseCurve {
+    /// The response to the given input magnitude, which is clamped to `0.0..=1.0`. No input always
+    /// gives no response.
+    #[must_use = "apply returns the new value and does not modify the original"]
+    pub fn apply(&self, magnitude: f32) -> f32 {
+        let magnitude = clamp(magnitude, &(0.0..=1.0));
+
+        if magnitude <= 0.0 {
+            return 0.0;
+        }
+
+        match self {
+            ResponseCurve::Constant => 1.0,
+            ResponseCurve::Linear => magnitude,
+            ResponseCurve::Quadratic => magnitude * magnitude,
+            ResponseCurve::Sampled(samples) => match samples.len() {
+                0 => magnitude,
+                1 => samples[0],
+                len => {
+                    let position = magnitude * (len - 1) as f32;
+                    let index = (position.floor() as usize).min(len - 2);
+                    let fraction = position - index as f32;
+                    samples[index] + (samples[index + 1] - samples[index]) * fraction
+                }
+            },
+        }
+    }
+}
+
+/// A plane through a point with a normal, which is the side of the plane that the camera is on.
+/// This is used as the camera's ground, which panning stays within and which cursor positions are
+/// projected onto, so that the ground need not be horizontal.
+///
+/// # Example
+///
+/// ```
+/// use bevy::math::Vec3;
+/// use goshawk::util::Plane;
+///
+/// let ground = Plane::new(Vec3::zero(), Vec3::new(0.0, 1.0, 0.5));
+/// let hit = ground.ray_intersection(Vec3::new(0.0, 10.0, 0.0), -Vec3::unit_y()).unwrap();
+/// assert!((hit - Vec3::zero()).length() < 1e-5);
+///
+/// // Directions along the plane keep their length
+/// let along = ground.project_direction(Vec3::unit_z());
+/// assert!(along.dot(ground.normal).abs() < 1e-5);
+/// assert!((along.length() - 1.0).abs() < 1e-5);
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Plane {
+    /// Any point on the plane
+    pub point: Vec3,
+    /// The direction perpendicular to the plane, pointing towards the camera's side. This must be
+    /// of unit length.
+    pub normal: Vec3,
+}
+
+impl Plane {
+    /// The plane through the given point with the given normal, which is normalized. The normal
+    /// must not be zero.
+    pub fn new(point: Vec3, normal: Vec3) -> Self {
+        debug_assert!(normal.length_squared() > 0.0, "the normal of a plane must not be zero");
+        Plane { point, normal: normal.normalize() }
+    }
+
+    /// The horizontal plane at the given height, facing upwards.
+    pub fn horizontal(y: f32) -> Self {
+        Plane { point: Vec3::new(0.0, y, 0.0), normal: Vec3::unit_y() }
+    }
+
+    /// The signed distance of the given point from the plane, which is positive on the side that
+    /// the normal points to.
+    pub fn distance_to(&self, point: Vec3) -> f32 {
+        (point - self.point).dot(self.normal)
+    }
+
+    /// The point on the plane closest to the given point.
+    pub fn project_point(&self, point: Vec3) -> Vec3 {
+        point - self.normal * self.distance_to(point)
+    }
+
+    /// The given direction moved into the plane, keeping its length. Directions perpendicular to
+    /// the plane have no such projection, and are returned as they are.
+    pub fn project_direction(&self, direction: Vec3) -> Vec3 {
+        let projected = direction - self.normal * direction.dot(self.normal);
+        let length = projected.length();
+
+        if length <= f32::EPSILON {
+            return direction;
+        }
+
+        projected * (direction.length() / length)
+    }
+
+    /// The given point moved vertically to the given signed distance from the plane, e.g to keep a
+    /// point at the same height above tilted ground after moving it horizontally. A point cannot
+    /// be moved vertically towards a vertical plane, so it is then returned as it is.
+    pub fn lift_to_distance(&self, point: Vec3, distance: f32) -> Vec3 {
+        if self.normal.y.abs() <= f32::EPSILON {
+            return point;
+        }
+
+        point + Vec3::unit_y() * ((distance - self.distance_to(point)) / self.normal.y)
+    }
+
+    /// The point at which a ray with the given origin and direction hits the plane from the side
+    /// that the normal points to, or `None` if it does not.
+    pub fn ray_intersection(&self, origin: Vec3, direction: Vec3) -> Option<Vec3> {
+        let height = self.distance_to(origin);
+        let approach = direction.dot(self.normal);
+
+        if approach >= 0.0 || height <= 0.0 {
+            return None;
+        }
+
+        Some(origin + direction * (height / -approach))
+    }
+}
+
+impl Default for Plane {
+    fn default() -> Self { Plane::horizontal(0.0) }
+}
+
+/// Clamp a value to within an inclusive range.
+///
+/// # Example
+///
+/// ```
+/// use goshawk::util::clamp;
+///
+/// assert_eq!(clamp(5.0, &(0.0..=1.0)), 1.0);
+/// assert_eq!(clamp(-5.0, &(0.0..=1.0)), 0.0);
+/// assert_eq!(clamp(0.5, &(0.0..=1.0)), 0.5);
+/// ```
+#[must_use = "clamp returns the new value and does not modify the original"]
+pub fn clamp(x: f32, range: &RangeInclusive<f32>) -> f32 {
+    if x > *range.end() {
+        *range.end()
+    } else if x < *range.start() {
+        *range.start()
+    } else {
+        x
+    }
+}
+
+/// Linearly map a value within `zone` to the corresponding value within `values`. Values outside
+/// of the zone are clamped to it first, so the result is always within `values`. If the zone has
+/// equal ends, the result is the start of `values`.
+///
+/// # Example
+///
+/// ```
+/// use goshawk::util::lerp_in_zone;
+///
+/// assert_eq!(lerp_in_zone(15.0, &(10.0..=20.0), &(0.0..=1.0)), 0.5);
+/// assert_eq!(lerp_in_zone(100.0, &(10.0..=20.0), &(0.0..=1.0)), 1.0);
+/// assert_eq!(lerp_in_zone(50.0, &(50.0..=50.0), &(0.0..=1.0)), 0.0);
+/// ```
+#[must_use = "lerp_in_zone returns the new value and does not modify the original"]
+pub fn lerp_in_zone(val: f32, zone: &RangeInclusive<f32>, values: &RangeInclusive<f32>) -> f32 {
+    let width = zone.end() - zone.start();
+
+    if width <= 0.0 {
+        return *values.start();
+    }
+
+    let in_zone = clamp(val, zone);
+    let normalised = (in_zone - *zone.start()) / width;
+    normalised * (values.end() - values.start()) + values.start()
+}
+
+/// Map a value within `zone` to the corresponding value within `values` along the given easing
+/// curve. Like [`lerp_in_zone`], values outside of the zone are clamped to it first, and if the zone
+/// has equal ends, the result is the start of `values`.
+///
+/// # Example
+///
+/// ```
+/// use goshawk::util::{ease_in_zone, Easing};
+///
+/// assert_eq!(ease_in_zone(15.0, &(10.0..=20.0), &(0.0..=2.0), Easing::Linear), 1.0);
+/// assert_eq!(ease_in_zone(12.0, &(10.0..=20.0), &(0.0..=1.0), Easing::EaseIn), 0.040000003);
+/// assert_eq!(ease_in_zone(50.0, &(50.0..=50.0), &(1.0..=2.0), Easing::SmoothStep), 1.0);
+/// ```
+#[must_use = "ease_in_zone returns the new value and does not modify the original"]
+pub fn ease_in_zone(val: f32, zone: &RangeInclusive<f32>, values: &RangeInclusive<f32>, easing: Easing) -> f32 {
+    let eased = easing.apply(lerp_in_zone(val, zone, &(0.0..=1.0)));
+    eased * (values.end() - values.start()) + values.start()
+}
+
+/// The signed angle in radians by which to turn from the yaw `from` to reach the yaw `to` the short
+/// way around the circle, accounting for wrapping at TAU. The result is within `-PI..=PI`. Any
+/// programmatic interpolation of yaw should go through this so that the camera never turns the
+/// long way around.
+///
+/// # Example
+///
+/// ```
+/// use goshawk::util::shortest_yaw_delta;
+/// use std::f32::consts::TAU;
+///
+/// let delta = shortest_yaw_delta(0.1, TAU - 0.1);
+/// assert!((delta + 0.2).abs() < 1e-5);
+/// ```
+#[must_use = "shortest_yaw_delta returns the delta and does not modify the original"]
+pub fn shortest_yaw_delta(from: f32, to: f32) -> f32 {
+    let diff = (to - from).rem_euclid(TAU);
+
+    if diff > PI {
+        diff - TAU
+    } else {
+        diff
+    }
+}
+
+/// The orthographic scale which frames the same view as a perspective camera with the given
+/// vertical field of view in radians, at the given distance from its target. The scale is the half
+/// height of the view in world units. The pitch does not affect the result, as both projections
+/// foreshorten the ground at the target in the same way. This allows switching between
+/// perspective and orthographic projections without a visual jump.
+///
+/// # Example
+///
+/// ```
+/// use goshawk::util::{ortho_scale_to_perspective, perspective_to_ortho_scale};
+/// use std::f32::consts::FRAC_PI_2;
+///
+/// let scale = perspective_to_ortho_scale(10.0, FRAC_PI_2);
+/// assert!((scale - 10.0).abs() < 1e-4);
+/// assert!((ortho_scale_to_perspective(scale, FRAC_PI_2) - 10.0).abs() < 1e-4);
+/// ```
+#[must_use = "perspective_to_ortho_scale returns the scale and does not modify the original"]
+pub fn perspective_to_ortho_scale(distance: f32, fov: f32) -> f32 {
+    distance * (fov / 2.0).tan()
+}
+
+/// The distance from its target at which a perspective camera with the given vertical field of
+/// view in radians frames the same view as an orthographic camera with the given scale. This is
+/// the inverse of [`perspective_to_ortho_scale`].
+#[must_use = "ortho_scale_to_perspective returns the distance and does not modify the original"]
+pub fn ortho_scale_to_perspective(scale: f32, fov: f32) -> f32 {
+    scale / (fov / 2.0).tan()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exponential_decay_slows_proportionally() {
+        let decel = Deceleration::default();
+        let (mut linear, mut exponential) = (10.0, 10.0);
+        let mut linear_steps = Vec::new();
+        let mut exponential_ratios = Vec::new();
+
+        for _ in 0..5 {
+            let (last_linear, last_exponential) = (linear, exponential);
+            decel.apply(&mut linear, 4.0, 4.0, DecelModel::Linear, 0.25);
+            decel.apply(&mut exponential, 4.0, 4.0, DecelModel::Exponential, 0.25);
+            linear_steps.push(last_linear - linear);
+            exponential_ratios.push(exponential / last_exponential);
+        }
+
+        // Linear decay loses the same amount every step, and exponential decay the same fraction
+        assert!(linear_steps.iter().all(|step| (step - 1.0).abs() < 1e-5));
+        assert!(exponential_ratios.iter().all(|ratio| (ratio - (-1.0f32).exp()).abs() < 1e-5));
+
+        // Exponential decay bleeds off high velocities faster, but never reaches zero
+        assert!(exponential < linear);
+        assert!(exponential > 0.0);
+    }
+
+    #[test]
+    fn deceleration_stops_at_zero_and_respects_input() {
+        let mut velocity = 0.5;
+        Deceleration::default().apply(&mut velocity, 10.0, 10.0, DecelModel::Linear, 1.0);
+        assert_eq!(velocity, 0.0);
+
+        let mut velocity = -0.5;
+        Deceleration::default().apply(&mut velocity, 10.0, 10.0, DecelModel::Linear, 1.0);
+        assert_eq!(velocity, 0.0);
+
+        // Without either flag, nothing opposes the motion
+        let mut velocity = 2.0;
+        Deceleration { pos: false, neg: false }.apply(&mut velocity, 10.0, 10.0, DecelModel::Linear, 1.0);
+        assert_eq!(velocity, 2.0);
+    }
+
+    #[test]
+    fn zone_helpers_clamp_and_map() {
+        assert_eq!(clamp(f32::INFINITY, &(-1.0..=1.0)), 1.0);
+        assert_eq!(lerp_in_zone(10.0, &(10.0..=20.0), &(4.0..=2.0)), 4.0);
+        assert_eq!(lerp_in_zone(20.0, &(10.0..=20.0), &(4.0..=2.0)), 2.0);
+        assert_eq!(lerp_in_zone(0.0, &(10.0..=20.0), &(4.0..=2.0)), 4.0);
+        assert_eq!(ease_in_zone(15.0, &(10.0..=20.0), &(0.0..=1.0), Easing::SmoothStep), 0.5);
+    }
+
+    #[test]
+    fn yaw_delta_takes_the_short_way_around() {
+        assert!((shortest_yaw_delta(0.1, TAU - 0.1) + 0.2).abs() < 1e-5);
+        assert!((shortest_yaw_delta(TAU - 0.1, 0.1) - 0.2).abs() < 1e-5);
+        assert!((shortest_yaw_delta(1.0, 2.0) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn ortho_scale_round_trips() {
+        for &fov in &[0.3, std::f32::consts::FRAC_PI_4, 1.5] {
+            for &distance in &[1.0, 25.0, 400.0] {
+                let scale = perspective_to_ortho_scale(distance, fov);
+                let round_trip = ortho_scale_to_perspective(scale, fov);
+                assert!((round_trip - distance).abs() < distance * 1e-5);
+            }
+        }
+    }
+}